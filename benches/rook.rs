@@ -1,11 +1,12 @@
+use chess::BoardGenerator;
+use chess::CaptureRules;
 use chess::Generator;
-use chess::{Benchmark, Board, BoardGenerator};
 use criterion::BenchmarkId;
-use criterion::Throughput;
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion};
 
 fn par_test(c: &mut Criterion) {
     let sizes = (1..10).map(|i| i * 32);
+    let rules = CaptureRules::new();
 
     let mut group = c.benchmark_group("Rook captures");
 
@@ -16,34 +17,34 @@ fn par_test(c: &mut Criterion) {
         group.bench_with_input(
             BenchmarkId::new("Single sequential", size),
             &size,
-            |b, &size| {
-                b.iter(|| board.get_rook_captures());
+            |b, _| {
+                b.iter(|| board.get_rook_captures(&rules));
             },
         );
 
         group.bench_with_input(
             BenchmarkId::new("Multiple sequential", size),
             &size,
-            |b, &size| {
-                b.iter(|| board.get_rooks_captures());
+            |b, _| {
+                b.iter(|| board.get_rooks_captures(&rules));
             },
         );
 
-        group.bench_with_input(
-            BenchmarkId::new("Single parallel", size),
-            &size,
-            |b, &size| {
-                b.iter(|| board.get_rook_captures_par());
-            },
-        );
+        group.bench_with_input(BenchmarkId::new("Single parallel", size), &size, |b, _| {
+            b.iter(|| board.get_rook_captures_par(&rules));
+        });
 
         group.bench_with_input(
             BenchmarkId::new("Multiple parallel", size),
             &size,
-            |b, &size| {
-                b.iter(|| board.get_rooks_captures_par());
+            |b, _| {
+                b.iter(|| board.get_rooks_captures_par(&rules));
             },
         );
+
+        group.bench_with_input(BenchmarkId::new("Multiple sweep", size), &size, |b, _| {
+            b.iter(|| board.get_rooks_captures_sweep(&rules));
+        });
     });
 
     group.finish();
@@ -51,23 +52,24 @@ fn par_test(c: &mut Criterion) {
 
 fn single(c: &mut Criterion) {
     let sizes = (1..10).map(|i| i * 32);
+    let rules = CaptureRules::new();
 
     let mut group = c.benchmark_group("Single only");
 
     sizes.for_each(|size| {
         let generator = BoardGenerator::new(size);
 
-        group.bench_with_input(BenchmarkId::new("Sequential", size), &size, |b, &size| {
-            b.iter(|| generator.generate().get_rook_captures());
+        group.bench_with_input(BenchmarkId::new("Sequential", size), &size, |b, _| {
+            b.iter(|| generator.generate().get_rook_captures(&rules));
         });
 
-        group.bench_with_input(BenchmarkId::new("Parallel", size), &size, |b, &size| {
-            b.iter(|| generator.generate().get_rook_captures_par());
+        group.bench_with_input(BenchmarkId::new("Parallel", size), &size, |b, _| {
+            b.iter(|| generator.generate().get_rook_captures_par(&rules));
         });
     });
 
     group.finish();
 }
 
-criterion_group!(benches, single);
+criterion_group!(benches, par_test, single);
 criterion_main!(benches);
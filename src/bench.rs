@@ -1,4 +1,4 @@
-use crate::{Board, PieceKind, Position};
+use crate::{Board, BoardStorage, PieceKind, Position};
 use rayon::ThreadPoolBuilder;
 
 use std::time::Instant;
@@ -17,11 +17,13 @@ pub struct BoardGenerator {
 
 impl BoardGenerator {
     pub fn new(board_size: u32) -> Self {
+        let count = (board_size as u64 * board_size as u64 / 8).min(u32::MAX as u64) as u32;
+
         BoardGenerator {
             board_size,
-            pawn_count: (board_size * board_size) / 8,
-            bishop_count: (board_size * board_size) / 8,
-            rook_count: (board_size * board_size) / 8,
+            pawn_count: count,
+            bishop_count: count,
+            rook_count: count,
         }
     }
 
@@ -39,37 +41,56 @@ impl BoardGenerator {
         self.rook_count = rook_count;
         self
     }
-}
 
-impl Generator for BoardGenerator {
-    type Output = Board;
-
-    fn generate(&self) -> Board {
-        let mut board = Board::new(self.board_size);
+    /// Generates a board using the storage backend `S`. When the pieces
+    /// cover less than half of the board, their squares are drawn at
+    /// random instead of being picked among all the squares of the
+    /// board, so that huge sparse boards can be generated. Panics if
+    /// the pieces don't fit on the board.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, BoardGenerator, SparseStorage};
+    /// let board: Board<SparseStorage> = BoardGenerator::new(1_000_000)
+    ///     .pawn_count(2000)
+    ///     .bishop_count(2000)
+    ///     .rook_count(2000)
+    ///     .generate_in();
+    ///
+    /// assert_eq!(board.pieces().count(), 6000);
+    /// ```
+    pub fn generate_in<S: BoardStorage>(&self) -> Board<S> {
+        let mut board = Board::with_storage(self.board_size);
         let mut random = rand::thread_rng();
 
-        let positions = Position::generate_unique_positions(
-            &mut random,
-            self.bishop_count + self.pawn_count + self.rook_count,
-            self.board_size,
+        let count = self.bishop_count as u64 + self.pawn_count as u64 + self.rook_count as u64;
+        let squares = self.board_size as u64 * self.board_size as u64;
+        assert!(
+            count <= squares.min(u32::MAX as u64),
+            "can't generate {} pieces on a board of {} squares",
+            count,
+            squares
         );
 
+        let sparse = count * 2 <= squares;
+        let count = count as u32;
+        let positions = if sparse {
+            Position::generate_sparse_unique_positions(&mut random, count, self.board_size)
+        } else {
+            Position::generate_unique_positions(&mut random, count, self.board_size)
+        };
+
+        let bishops = self.bishop_count as usize;
+        let pawns = bishops + self.pawn_count as usize;
         let pieces = &[
-            (PieceKind::Bishop, &positions[0..self.bishop_count as usize]),
-            (
-                PieceKind::Pawn,
-                &positions
-                    [self.bishop_count as usize..(self.bishop_count + self.pawn_count) as usize],
-            ),
-            (
-                PieceKind::Rook,
-                &positions[(self.bishop_count + self.pawn_count) as usize..],
-            ),
+            (PieceKind::Bishop, &positions[0..bishops]),
+            (PieceKind::Pawn, &positions[bishops..pawns]),
+            (PieceKind::Rook, &positions[pawns..]),
         ];
 
         board.set_pieces(
             pieces
-                .into_iter()
+                .iter()
                 .flat_map(|(k, ps)| ps.iter().map(|p| (*k, *p)).collect::<Vec<_>>())
                 .collect(),
         );
@@ -77,11 +98,27 @@ impl Generator for BoardGenerator {
     }
 }
 
+impl Generator for BoardGenerator {
+    type Output = Board;
+
+    fn generate(&self) -> Board {
+        self.generate_in()
+    }
+}
+
+type BenchFunction<U> = Box<dyn FnMut(&U) + Sync + Send>;
+
 pub struct Benchmark<U: Sync + Send> {
     sizes: Option<Vec<usize>>,
     threads: Option<Vec<usize>>,
     runs: usize,
-    functions: Vec<(String, Box<dyn FnMut(&U) -> () + Sync + Send>)>,
+    functions: Vec<(String, BenchFunction<U>)>,
+}
+
+impl<U: Sync + Send> Default for Benchmark<U> {
+    fn default() -> Self {
+        Benchmark::new()
+    }
 }
 
 impl<U: Sync + Send> Benchmark<U> {
@@ -109,11 +146,7 @@ impl<U: Sync + Send> Benchmark<U> {
         self
     }
 
-    pub fn add_function(
-        mut self,
-        function: Box<dyn FnMut(&U) -> () + Sync + Send>,
-        name: String,
-    ) -> Self {
+    pub fn add_function(mut self, function: BenchFunction<U>, name: String) -> Self {
         self.functions.push((name, function));
         self
     }
@@ -166,6 +199,7 @@ impl<U: Sync + Send> Benchmark<U> {
     }
 }
 
+/// The times measured by a benchmark.
 pub struct BenchmarkResult {
     data: Vec<Vec<Vec<Vec<u64>>>>,
     functions: Vec<String>,
@@ -187,4 +221,25 @@ impl BenchmarkResult {
             sizes,
         }
     }
+
+    /// Returns the times measured, in nanoseconds, indexed by number of
+    /// threads, then by size, then by run, then by function.
+    pub fn data(&self) -> &[Vec<Vec<Vec<u64>>>] {
+        &self.data
+    }
+
+    /// Returns the names of the functions measured.
+    pub fn functions(&self) -> &[String] {
+        &self.functions
+    }
+
+    /// Returns the numbers of threads the functions were run with.
+    pub fn threads(&self) -> &[usize] {
+        &self.threads
+    }
+
+    /// Returns the sizes of the boards the functions were run on.
+    pub fn sizes(&self) -> &[usize] {
+        &self.sizes
+    }
 }
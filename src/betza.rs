@@ -0,0 +1,204 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{Mode, Movement};
+
+/// The ways a Betza notation can be invalid.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum BetzaErrorKind {
+    /// The notation holds no atom at all
+    Empty,
+    /// A character that is neither an atom, a modifier nor a range
+    UnknownAtom(char),
+    /// A lowercase prefix other than `m` and `c`, before a modifier or
+    /// an atom
+    UnknownModifier(char),
+    /// The notation ends with modifiers that apply to no atom
+    MissingAtom,
+    /// A range that doesn't follow an atom
+    MisplacedRange,
+    /// A range too large to be represented
+    RangeTooLarge,
+}
+
+/// An error met while parsing a Betza notation, along with the position
+/// of the offending character, counted in characters from the start of
+/// the notation.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct BetzaError {
+    position: usize,
+    kind: BetzaErrorKind,
+}
+
+impl BetzaError {
+    /// Returns the position of the offending character.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns what is wrong with the notation.
+    pub fn kind(&self) -> BetzaErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for BetzaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            BetzaErrorKind::Empty => write!(f, "empty Betza notation"),
+            BetzaErrorKind::UnknownAtom(c) => {
+                write!(f, "unknown atom '{}' at position {}", c, self.position)
+            }
+            BetzaErrorKind::UnknownModifier(c) => {
+                write!(f, "unknown modifier '{}' at position {}", c, self.position)
+            }
+            BetzaErrorKind::MissingAtom => {
+                write!(
+                    f,
+                    "missing atom after modifiers at position {}",
+                    self.position
+                )
+            }
+            BetzaErrorKind::MisplacedRange => {
+                write!(f, "range without an atom at position {}", self.position)
+            }
+            BetzaErrorKind::RangeTooLarge => {
+                write!(f, "range too large at position {}", self.position)
+            }
+        }
+    }
+}
+
+impl Error for BetzaError {}
+
+/// Returns the leaps of an atom, and whether it slides by default.
+fn atom(c: char) -> Option<(Vec<(i32, i32)>, bool)> {
+    let leaper = |offset| Some((vec![offset], false));
+
+    match c {
+        'W' => leaper((0, 1)),
+        'F' => leaper((1, 1)),
+        'D' => leaper((0, 2)),
+        'N' => leaper((1, 2)),
+        'A' => leaper((2, 2)),
+        'H' => leaper((0, 3)),
+        'C' => leaper((1, 3)),
+        'Z' => leaper((2, 3)),
+        'G' => leaper((3, 3)),
+        'R' => Some((vec![(0, 1)], true)),
+        'B' => Some((vec![(1, 1)], true)),
+        'Q' => Some((vec![(0, 1), (1, 1)], true)),
+        'K' => Some((vec![(0, 1), (1, 1)], false)),
+        _ => None,
+    }
+}
+
+impl Movement {
+    /// Parses a piece described in Betza notation. The notation is a
+    /// sequence of atoms, each optionally preceded by modifiers and
+    /// followed by a range:
+    /// - the leapers `W`, `F`, `D`, `N`, `A`, `H`, `C`, `Z` and `G`, and
+    ///   the shorthands `R`, `B`, `Q` and `K`,
+    /// - a leaper atom written twice, such as `NN`, rides without limit,
+    /// - the `m` modifier only lets the atom move, and the `c` modifier
+    ///   only lets it capture,
+    /// - a number after an atom repeats it at most that many times, `0`
+    ///   meaning without limit, as in `W3` or `N0`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{BetzaErrorKind, Board, CaptureRules, Movement, PieceKind, Position};
+    /// let mut board = Board::new(8);
+    /// board.add_rook(Position::new(3, 3, 8));
+    /// board.add_pawn(Position::new(3, 6, 8));
+    /// board.add_pawn(Position::new(5, 5, 8));
+    ///
+    /// let rules = CaptureRules::new();
+    /// let movement = Movement::from_betza("mRcB").unwrap();
+    /// assert_eq!(board.get_captures_with(PieceKind::Rook, &movement, &rules), 1);
+    ///
+    /// let error = Movement::from_betza("NX").unwrap_err();
+    /// assert_eq!(error.position(), 1);
+    ///
+    /// let error = Movement::from_betza("Wn").unwrap_err();
+    /// assert_eq!(error.kind(), BetzaErrorKind::UnknownAtom('n'));
+    /// ```
+    pub fn from_betza(notation: &str) -> Result<Movement, BetzaError> {
+        let chars = notation.chars().collect::<Vec<_>>();
+        let error = |position, kind| Err(BetzaError { position, kind });
+
+        if chars.is_empty() {
+            return error(0, BetzaErrorKind::Empty);
+        }
+
+        let mut movement = Movement::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            // Modifiers
+            let (mut moves, mut captures) = (false, false);
+            while i < chars.len() && chars[i].is_lowercase() {
+                match chars[i] {
+                    'm' => moves = true,
+                    'c' => captures = true,
+                    // Another lowercase letter is only taken for a modifier
+                    // if a modifier or an atom follows it
+                    c => {
+                        let prefix = match chars.get(i + 1) {
+                            Some('m') | Some('c') => true,
+                            Some(&next) => atom(next).is_some(),
+                            None => false,
+                        };
+                        if prefix {
+                            return error(i, BetzaErrorKind::UnknownModifier(c));
+                        }
+                        return error(i, BetzaErrorKind::UnknownAtom(c));
+                    }
+                }
+                i += 1;
+            }
+
+            // Atom
+            let (offsets, slides) = match chars.get(i) {
+                None => return error(i, BetzaErrorKind::MissingAtom),
+                Some(c) if c.is_ascii_digit() => return error(i, BetzaErrorKind::MisplacedRange),
+                Some(&c) => match atom(c) {
+                    Some(a) => a,
+                    None => return error(i, BetzaErrorKind::UnknownAtom(c)),
+                },
+            };
+            let mut range = if slides { None } else { Some(1) };
+            i += 1;
+
+            // A doubled leaper rides
+            if !slides && offsets.len() == 1 && chars.get(i) == chars.get(i - 1) {
+                range = None;
+                i += 1;
+            }
+
+            // Range
+            let digits = chars[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+            if digits > 0 {
+                let number = chars[i..i + digits].iter().collect::<String>();
+                range = match number.parse::<u32>() {
+                    Ok(0) => None,
+                    Ok(n) => Some(n),
+                    Err(_) => return error(i, BetzaErrorKind::RangeTooLarge),
+                };
+                i += digits;
+            }
+
+            let mode = match (moves, captures) {
+                (true, false) => Mode::MoveOnly,
+                (false, true) => Mode::CaptureOnly,
+                _ => Mode::Both,
+            };
+
+            movement = offsets.into_iter().fold(movement, |m, o| {
+                m.and(Movement::rider(o, range).with_mode(mode))
+            });
+        }
+
+        Ok(movement)
+    }
+}
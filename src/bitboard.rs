@@ -0,0 +1,352 @@
+use crate::storage::walk_line;
+use crate::{BoardStorage, Direction, PieceKind, Position};
+
+/// A square grid of bits, stored row by row. Each row takes as many
+/// `u64` words as needed to hold the board width: boards up to 64
+/// squares wide use a single `u64` per row, larger boards use an
+/// array of words per row.
+#[derive(Clone)]
+struct BitPlane {
+    stride: usize,
+    words: Vec<u64>,
+}
+
+impl BitPlane {
+    fn new(size: u32) -> Self {
+        let stride = (size as usize).div_ceil(64);
+
+        BitPlane {
+            stride,
+            words: vec![0; stride * size as usize],
+        }
+    }
+
+    fn word(&self, row: u32, col: u32) -> (usize, u64) {
+        let index = row as usize * self.stride + col as usize / 64;
+        (index, 1 << (col % 64))
+    }
+
+    fn get(&self, row: u32, col: u32) -> bool {
+        let (index, mask) = self.word(row, col);
+        self.words[index] & mask != 0
+    }
+
+    fn set(&mut self, row: u32, col: u32) {
+        let (index, mask) = self.word(row, col);
+        self.words[index] |= mask;
+    }
+
+    fn unset(&mut self, row: u32, col: u32) {
+        let (index, mask) = self.word(row, col);
+        self.words[index] &= !mask;
+    }
+
+    fn clear(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = 0);
+    }
+
+    /// Returns the column of the first set bit of a row strictly
+    /// after `col`, using trailing zero counts.
+    fn next(&self, row: u32, col: u32) -> Option<u32> {
+        let start = col as usize + 1;
+        let words = &self.words[row as usize * self.stride..(row as usize + 1) * self.stride];
+
+        let mut w = start / 64;
+        if w >= self.stride {
+            return None;
+        }
+
+        let mut word = words[w] & (!0 << (start % 64));
+        loop {
+            if word != 0 {
+                return Some((w * 64 + word.trailing_zeros() as usize) as u32);
+            }
+
+            w += 1;
+            if w == self.stride {
+                return None;
+            }
+            word = words[w];
+        }
+    }
+
+    /// Returns the column of the last set bit of a row strictly
+    /// before `col`, using leading zero counts.
+    fn prev(&self, row: u32, col: u32) -> Option<u32> {
+        if col == 0 {
+            return None;
+        }
+
+        let end = col as usize - 1;
+        let words = &self.words[row as usize * self.stride..(row as usize + 1) * self.stride];
+
+        let mut w = end / 64;
+        let mut word = words[w] & (!0 >> (63 - end % 64));
+        loop {
+            if word != 0 {
+                return Some((w * 64 + 63 - word.leading_zeros() as usize) as u32);
+            }
+
+            if w == 0 {
+                return None;
+            }
+            w -= 1;
+            word = words[w];
+        }
+    }
+
+    /// Returns the row and column of every set bit.
+    fn ones(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        let stride = self.stride;
+
+        self.words
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| **w != 0)
+            .flat_map(move |(i, &w)| {
+                let (row, base) = (i / stride, (i % stride) * 64);
+
+                (0..64)
+                    .filter(move |b| w & (1 << b) != 0)
+                    .map(move |b| (row as u32, (base + b) as u32))
+            })
+    }
+}
+
+/// Stores the pieces as bitsets: one per kind of piece, plus the set of
+/// occupied squares kept both row by row and column by column. Finding
+/// the nearest piece along a rank or a file is a bit scan over the
+/// words of a single row (or column), instead of a walk over every
+/// square of the line.
+///
+/// # Examples
+/// ```
+/// # use chess::{BitStorage, Board, CaptureRules, Position};
+/// let mut board = Board::<BitStorage>::with_storage(130);
+/// board.add_rook(Position::new(64, 64, 130));
+/// board.add_pawn(Position::new(64, 0, 130));
+/// board.add_pawn(Position::new(64, 129, 130));
+/// board.add_bishop(Position::new(63, 64, 130));
+/// board.add_pawn(Position::new(0, 64, 130));
+///
+/// assert_eq!(board.get_rooks_captures(&CaptureRules::new()), 2);
+/// ```
+#[derive(Clone)]
+pub struct BitStorage {
+    size: u32,
+    rows: BitPlane,
+    columns: BitPlane,
+    kinds: Vec<(PieceKind, BitPlane)>,
+}
+
+impl BoardStorage for BitStorage {
+    fn new(size: u32) -> Self {
+        BitStorage {
+            size,
+            rows: BitPlane::new(size),
+            columns: BitPlane::new(size),
+            kinds: Vec::new(),
+        }
+    }
+
+    fn get(&self, position: &Position) -> Option<PieceKind> {
+        let (row, col) = (position.row(), position.col());
+
+        if !self.rows.get(row, col) {
+            return None;
+        }
+
+        self.kinds
+            .iter()
+            .find(|(_, plane)| plane.get(row, col))
+            .map(|(k, _)| *k)
+    }
+
+    fn insert(&mut self, piece: PieceKind, position: Position) {
+        let (row, col) = (position.row(), position.col());
+
+        self.rows.set(row, col);
+        self.columns.set(col, row);
+
+        match self.kinds.iter_mut().find(|(k, _)| *k == piece) {
+            Some((_, plane)) => plane.set(row, col),
+            None => {
+                let mut plane = BitPlane::new(self.size);
+                plane.set(row, col);
+                self.kinds.push((piece, plane));
+            }
+        }
+    }
+
+    fn remove(&mut self, position: &Position) -> Option<PieceKind> {
+        let kind = self.get(position)?;
+        let (row, col) = (position.row(), position.col());
+
+        self.rows.unset(row, col);
+        self.columns.unset(col, row);
+        self.kinds
+            .iter_mut()
+            .filter(|(k, _)| *k == kind)
+            .for_each(|(_, plane)| plane.unset(row, col));
+
+        Some(kind)
+    }
+
+    fn clear(&mut self) {
+        self.rows.clear();
+        self.columns.clear();
+        self.kinds.iter_mut().for_each(|(_, plane)| plane.clear());
+    }
+
+    fn pieces(&self) -> Box<dyn Iterator<Item = (PieceKind, Position)> + '_> {
+        let size = self.size;
+
+        Box::new(self.kinds.iter().flat_map(move |(k, plane)| {
+            plane
+                .ones()
+                .map(move |(row, col)| (*k, Position::new(row, col, size)))
+        }))
+    }
+
+    fn first_piece(
+        &self,
+        position: &Position,
+        direction: Direction,
+        size: u32,
+    ) -> Option<(PieceKind, Position)> {
+        let (row, col) = (position.row(), position.col());
+
+        let found = match direction {
+            Direction::East => self.rows.next(row, col).map(|c| (row, c)),
+            Direction::West => self.rows.prev(row, col).map(|c| (row, c)),
+            Direction::South => self.columns.next(col, row).map(|r| (r, col)),
+            Direction::North => self.columns.prev(col, row).map(|r| (r, col)),
+            // Diagonals are not indexed, so they are walked square by square
+            _ => return walk_line(self, position, direction, size),
+        };
+
+        found.map(|(row, col)| {
+            let p = Position::new(row, col, size);
+            (self.get(&p).unwrap(), p)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitPlane;
+    use crate::{BitStorage, Board, CaptureRules, Direction, PieceKind, Position};
+
+    #[test]
+    fn bit_scans_cross_words() {
+        let mut plane = BitPlane::new(130);
+        [0, 63, 64, 129].iter().for_each(|col| plane.set(5, *col));
+
+        assert_eq!(plane.next(5, 0), Some(63));
+        assert_eq!(plane.next(5, 63), Some(64));
+        assert_eq!(plane.next(5, 64), Some(129));
+        assert_eq!(plane.next(5, 100), Some(129));
+        assert_eq!(plane.next(5, 129), None);
+
+        assert_eq!(plane.prev(5, 129), Some(64));
+        assert_eq!(plane.prev(5, 64), Some(63));
+        assert_eq!(plane.prev(5, 63), Some(0));
+        assert_eq!(plane.prev(5, 0), None);
+
+        // The other rows are left untouched
+        assert_eq!(plane.next(4, 0), None);
+        assert_eq!(plane.prev(6, 129), None);
+    }
+
+    #[test]
+    fn bit_scans_on_a_single_square() {
+        let mut plane = BitPlane::new(1);
+        assert_eq!(plane.next(0, 0), None);
+        assert_eq!(plane.prev(0, 0), None);
+
+        plane.set(0, 0);
+        assert!(plane.get(0, 0));
+        assert_eq!(plane.next(0, 0), None);
+        assert_eq!(plane.prev(0, 0), None);
+    }
+
+    #[test]
+    fn first_piece_at_the_edges() {
+        let size = 130;
+        let mut board = Board::<BitStorage>::with_storage(size);
+        board.add_rook(Position::new(0, 0, size));
+        board.add_pawn(Position::new(0, 129, size));
+        board.add_pawn(Position::new(129, 0, size));
+
+        let rook = Position::new(0, 0, size);
+        assert_eq!(
+            board.first_piece(&rook, Direction::East),
+            Some((PieceKind::Pawn, Position::new(0, 129, size)))
+        );
+        assert_eq!(
+            board.first_piece(&rook, Direction::South),
+            Some((PieceKind::Pawn, Position::new(129, 0, size)))
+        );
+        assert_eq!(board.first_piece(&rook, Direction::West), None);
+        assert_eq!(board.first_piece(&rook, Direction::North), None);
+        assert_eq!(board.get_rooks_captures(&CaptureRules::new()), 2);
+    }
+
+    #[test]
+    fn first_piece_next_to_a_word_boundary() {
+        let size = 130;
+        let mut board = Board::<BitStorage>::with_storage(size);
+        board.add_rook(Position::new(63, 63, size));
+        board.add_pawn(Position::new(63, 64, size));
+        board.add_pawn(Position::new(64, 63, size));
+        board.add_pawn(Position::new(62, 63, size));
+
+        let rook = Position::new(63, 63, size);
+        assert_eq!(
+            board.first_piece(&rook, Direction::East),
+            Some((PieceKind::Pawn, Position::new(63, 64, size)))
+        );
+        assert_eq!(
+            board.first_piece(&rook, Direction::South),
+            Some((PieceKind::Pawn, Position::new(64, 63, size)))
+        );
+        assert_eq!(board.get_rooks_captures(&CaptureRules::new()), 3);
+
+        board.remove_piece(&Position::new(63, 64, size));
+        assert_eq!(board.first_piece(&rook, Direction::East), None);
+        assert_eq!(board.get_rooks_captures(&CaptureRules::new()), 2);
+    }
+
+    #[test]
+    fn first_piece_along_a_diagonal() {
+        let mut board = Board::<BitStorage>::with_storage(8);
+        board.add_pawn(Position::new(0, 2, 8));
+
+        assert_eq!(
+            board.first_piece(&Position::new(2, 0, 8), Direction::NorthEast),
+            Some((PieceKind::Pawn, Position::new(0, 2, 8)))
+        );
+        assert_eq!(
+            board.first_piece(&Position::new(2, 0, 8), Direction::SouthEast),
+            None
+        );
+    }
+
+    #[test]
+    fn empty_boards() {
+        let board = Board::<BitStorage>::with_storage(8);
+        assert_eq!(board.pieces().count(), 0);
+        assert_eq!(
+            board.first_piece(&Position::new(3, 3, 8), Direction::East),
+            None
+        );
+
+        let mut board = Board::<BitStorage>::with_storage(1);
+        board.add_rook(Position::new(0, 0, 1));
+        assert_eq!(
+            board.get_piece(&Position::new(0, 0, 1)),
+            Some(PieceKind::Rook)
+        );
+        assert_eq!(board.get_rooks_captures(&CaptureRules::new()), 0);
+    }
+}
@@ -1,44 +1,167 @@
 use rayon::prelude::*;
 use std::collections::HashSet;
-use std::io::BufRead;
 
-use crate::{Direction, PieceKind, Position};
+use crate::bitset::{AtomicBitSet, BitSet};
+use crate::{
+    BoardStorage, CaptureRules, Direction, ListStorage, Move, MoveError, Movement, PieceKind,
+    PieceSet, Position, Ray,
+};
 
-pub struct Board {
+/// Returns the indices of the pieces that the rooks can capture along
+/// a line, given the pieces of the line in order: a piece is captured
+/// when a rook sees it from either side, through the pieces that don't
+/// block.
+pub(crate) fn line_captures<I>(line: I, rules: &CaptureRules) -> Vec<usize>
+where
+    I: Iterator<Item = (usize, PieceKind)>,
+{
+    let line = line.collect::<Vec<_>>();
+    let mut captured = vec![false; line.len()];
+    let attacks = rules.is_attacking(PieceKind::Rook);
+
+    // Sweep the line forwards then backwards, remembering whether a rook
+    // can see the current square
+    let mut sweep = |order: &mut dyn Iterator<Item = usize>| {
+        let mut seen = false;
+        order.for_each(|i| {
+            let k = line[i].1;
+            if seen && rules.is_capturable(k) {
+                captured[i] = true;
+            }
+            if rules.is_blocking(k) {
+                seen = false;
+            }
+            if attacks && k == PieceKind::Rook {
+                seen = true;
+            }
+        });
+    };
+    sweep(&mut (0..line.len()));
+    sweep(&mut (0..line.len()).rev());
+
+    line.iter()
+        .zip(captured)
+        .filter_map(|(&(i, _), c)| if c { Some(i) } else { None })
+        .collect()
+}
+
+/// The ways the parallel capture count can get rid of pawns captured
+/// by several rooks.
+#[derive(Debug, Copy, Clone)]
+pub enum Deduplication {
+    /// Every rook collects its captures in a `HashSet`, and the sets are
+    /// merged two by two.
+    HashSet,
+    /// All the rooks mark their captures in a single bitset of the
+    /// squares of the board, shared between the threads.
+    AtomicBitset,
+    /// Every thread marks captures in its own bitset of the squares of
+    /// the board, and the bitsets are OR-ed at the end.
+    LocalBitsets,
+}
+
+/// A square chessboard. The pieces are kept in a `BoardStorage`
+/// backend, which defaults to a plain list of pieces.
+#[derive(Clone)]
+pub struct Board<S: BoardStorage = ListStorage> {
     size: u32,
-    pieces: Vec<(PieceKind, Position)>,
+    pieces: S,
+    /// The moves played, the last one at the end
+    history: Vec<Move>,
+    /// The moves undone, the last one undone at the end
+    future: Vec<Move>,
 }
 
 impl Board {
     /// Creates a new empty board of a given size.
     pub fn new(size: u32) -> Board {
+        Board::with_storage(size)
+    }
+
+    pub fn with_capacity(size: u32, pieces: usize) -> Board {
         Board {
             size,
-            pieces: Vec::new(),
+            pieces: ListStorage::with_capacity(size, pieces),
+            history: Vec::new(),
+            future: Vec::new(),
         }
     }
+}
 
-    pub fn with_capacity(size: u32, pieces: usize) -> Board {
+impl<S: BoardStorage> Board<S> {
+    /// Creates a new empty board of a given size, using the storage
+    /// backend `S`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, CaptureRules, GridStorage, Position};
+    /// let mut board = Board::<GridStorage>::with_storage(8);
+    /// board.add_rook(Position::new(2, 3, 8));
+    /// board.add_pawn(Position::new(2, 6, 8));
+    ///
+    /// assert_eq!(board.get_rooks_captures(&CaptureRules::new()), 1);
+    /// ```
+    pub fn with_storage(size: u32) -> Self {
         Board {
             size,
-            pieces: Vec::with_capacity(pieces),
+            pieces: S::new(size),
+            history: Vec::new(),
+            future: Vec::new(),
         }
     }
 
+    /// Moves all the pieces of the board into another storage backend.
+    pub fn into_storage<T: BoardStorage>(self) -> Board<T> {
+        let mut pieces = T::with_capacity(self.size, 0);
+        self.pieces.pieces().for_each(|(k, p)| pieces.insert(k, p));
+
+        Board {
+            size: self.size,
+            pieces,
+            history: self.history,
+            future: self.future,
+        }
+    }
+
+    /// Returns the size of the board.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns all the pieces on the board, in no particular order.
+    pub fn pieces(&self) -> impl Iterator<Item = (PieceKind, Position)> + '_ {
+        self.pieces.pieces()
+    }
+
     pub fn set_pieces(&mut self, pieces: Vec<(PieceKind, Position)>) {
-        self.pieces = pieces;
+        self.forget_moves();
+        self.pieces.clear();
+        pieces
+            .into_iter()
+            .for_each(|(k, p)| self.pieces.insert(k, p));
     }
+
     pub fn clear(&mut self) {
+        self.forget_moves();
         self.pieces.clear();
     }
 
+    /// Clears the history of moves. Undoing moves played before pieces
+    /// were added or removed outside of moves could leave the board in
+    /// an inconsistent state.
+    fn forget_moves(&mut self) {
+        self.history.clear();
+        self.future.clear();
+    }
+
     /// Adds a piece on the specified square on the board.
     ///
     /// # Panics
     /// The function panics if the square is already occupied.
     pub fn add_piece(&mut self, piece: PieceKind, position: Position) {
+        self.forget_moves();
         match self.get_piece(&position) {
-            None => self.pieces.push((piece, position)),
+            None => self.pieces.insert(piece, position),
             Some(_) => panic!("Trying to add a piece on an already occupied square."),
         }
     }
@@ -50,10 +173,11 @@ impl Board {
     /// The function panics if:
     /// - The square is already occupied,
     pub fn add_rook(&mut self, position: Position) {
+        self.forget_moves();
         if self.get_piece(&position).is_some() {
             panic!("Trying to add a piece on an already occupied square.");
         } else {
-            self.pieces.push((PieceKind::Rook, position));
+            self.pieces.insert(PieceKind::Rook, position);
         }
     }
 
@@ -63,10 +187,11 @@ impl Board {
     /// # Panics
     /// The function panics if the square is already occupied.
     pub fn add_bishop(&mut self, position: Position) {
+        self.forget_moves();
         if self.get_piece(&position).is_some() {
             panic!("Trying to add a piece on an already occupied square.")
         } else {
-            self.pieces.push((PieceKind::Bishop, position))
+            self.pieces.insert(PieceKind::Bishop, position)
         }
     }
 
@@ -76,71 +201,42 @@ impl Board {
     /// # Panics
     /// The function panics if the square is already occupied.
     pub fn add_pawn(&mut self, position: Position) {
+        self.forget_moves();
         if self.get_piece(&position).is_some() {
             panic!("Trying to add a piece on an already occupied square.")
         } else {
-            self.pieces.push((PieceKind::Pawn, position))
+            self.pieces.insert(PieceKind::Pawn, position)
         }
     }
 
     /// Returns true if the rook is present on the board, false otherwise.
     pub fn is_rook_present(&self) -> bool {
-        self.pieces.iter().any(|(k, _)| match k {
-            PieceKind::Rook => true,
-            _ => false,
-        })
+        self.pieces
+            .pieces()
+            .any(|(k, _)| matches!(k, PieceKind::Rook))
     }
 
     /// Removes a piece on the specified square, if any.
     pub fn remove_piece(&mut self, position: &Position) {
-        self.pieces.retain(|(_, p)| p != position)
-    }
-
-    /// Recreates a board from a text file. This function does not
-    /// perform any checks on the I/O, and thus can panic for any
-    /// I/O error that may happen during the parsing of the file.
-    pub fn from_file<B: BufRead>(r: B) -> Self {
-        let mut lines = r.lines();
-        let board_size: u32 = lines.next().unwrap().unwrap().parse().unwrap();
-
-        let pieces = lines
-            .enumerate()
-            .flat_map(|(row, l)| {
-                l.unwrap()
-                    .chars()
-                    .enumerate()
-                    .filter_map(|(col, c)| match c {
-                        'p' => Some((
-                            PieceKind::Pawn,
-                            Position::new(row as u32, col as u32, board_size),
-                        )),
-                        'R' => Some((
-                            PieceKind::Rook,
-                            Position::new(row as u32, col as u32, board_size),
-                        )),
-                        'B' => Some((
-                            PieceKind::Bishop,
-                            Position::new(row as u32, col as u32, board_size),
-                        )),
-                        _ => None,
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
-
-        Board {
-            size: board_size,
-            pieces,
-        }
+        self.forget_moves();
+        self.pieces.remove(position);
     }
 
     /// Returns the kind of piece present on a certain position
     /// on the board, if any.
     pub fn get_piece(&self, position: &Position) -> Option<PieceKind> {
-        self.pieces
-            .iter()
-            .find(|(_, p)| p == position)
-            .map(|(k, _)| *k)
+        self.pieces.get(position)
+    }
+
+    /// Returns the first piece met when walking from a position
+    /// (excluded) towards the edge of the board, along with its
+    /// position. The cost depends on the storage backend.
+    pub fn first_piece(
+        &self,
+        position: &Position,
+        direction: Direction,
+    ) -> Option<(PieceKind, Position)> {
+        self.pieces.first_piece(position, direction, self.size)
     }
 
     /// Returns the position of the rook on the board
@@ -150,169 +246,668 @@ impl Board {
     /// a rook.
     pub fn get_rook_position(&self) -> Position {
         self.pieces
-            .iter()
-            .find(|(k, _)| match k {
-                PieceKind::Rook => true,
-                _ => false,
-            })
-            .map(|(_, p)| *p)
+            .pieces()
+            .find(|(k, _)| matches!(k, PieceKind::Rook))
+            .map(|(_, p)| p)
             .expect("No rook present on the board.")
     }
 
     pub fn get_rooks_positions(&self) -> Vec<Position> {
         self.pieces
-            .iter()
+            .pieces()
             .filter_map(|(k, p)| match k {
-                PieceKind::Rook => Some(*p),
+                PieceKind::Rook => Some(p),
                 _ => None,
             })
             .collect()
     }
 
-    /// Computes the number of pawns the rook can capture in the
-    /// board's current configuration.
-    pub fn get_rook_captures(&self) -> usize {
+    /// Returns the rooks with a line of sight to a position, through the
+    /// pieces that don't block, by casting the lines of the position
+    /// outwards. When the position holds a capturable piece, these are
+    /// the rooks that can capture it.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, CaptureRules, Position};
+    /// // The second example of the readme
+    /// let mut board = Board::new(8);
+    /// board.add_rook(Position::new(1, 5, 8));
+    /// board.add_rook(Position::new(3, 1, 8));
+    /// board.add_rook(Position::new(5, 4, 8));
+    /// board.add_pawn(Position::new(1, 4, 8));
+    /// board.add_pawn(Position::new(1, 7, 8));
+    /// board.add_pawn(Position::new(3, 5, 8));
+    /// board.add_pawn(Position::new(5, 7, 8));
+    /// board.add_pawn(Position::new(7, 4, 8));
+    /// board.add_bishop(Position::new(5, 6, 8));
+    ///
+    /// let rules = CaptureRules::new();
+    /// let mut attackers = board.attackers_of(&Position::new(1, 4, 8), &rules);
+    /// attackers.sort_by_key(|p| p.row());
+    ///
+    /// assert_eq!(attackers, vec![Position::new(1, 5, 8), Position::new(5, 4, 8)]);
+    /// assert!(board.attackers_of(&Position::new(5, 7, 8), &rules).is_empty());
+    /// ```
+    pub fn attackers_of(&self, position: &Position, rules: &CaptureRules) -> Vec<Position> {
+        if !rules.is_attacking(PieceKind::Rook) {
+            return Vec::new();
+        }
+
+        Direction::orthogonal()
+            .into_iter()
+            .flat_map(|d| rules.sight(*position, |p| self.first_piece(p, d)))
+            .filter_map(|(k, p)| match k {
+                PieceKind::Rook => Some(p),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the rooks with a line of sight to every position of a
+    /// list, in the same order, as `attackers_of` does. The positions
+    /// are considered in parallel.
+    pub fn attackers_of_par(
+        &self,
+        positions: &[Position],
+        rules: &CaptureRules,
+    ) -> Vec<Vec<Position>> {
+        positions
+            .par_iter()
+            .map(|p| self.attackers_of(p, rules))
+            .collect()
+    }
+
+    /// Returns the pieces a rook standing on a position can capture
+    /// along a line, following the rules.
+    pub(crate) fn rook_line(
+        &self,
+        start: &Position,
+        direction: Direction,
+        rules: &CaptureRules,
+    ) -> Vec<Position> {
+        if !rules.is_attacking(PieceKind::Rook) {
+            return Vec::new();
+        }
+
+        rules.walk(*start, |p| self.first_piece(p, direction))
+    }
+
+    /// Returns the pieces a rook standing on a position can capture.
+    fn rook_targets<'a>(
+        &'a self,
+        start: &'a Position,
+        rules: &'a CaptureRules,
+    ) -> impl Iterator<Item = Position> + 'a {
+        Direction::orthogonal()
+            .into_iter()
+            .flat_map(move |d| self.rook_line(start, d, rules))
+    }
+
+    /// Computes the number of pieces the rook can capture in the
+    /// board's current configuration, following the rules.
+    pub fn get_rook_captures(&self, rules: &CaptureRules) -> usize {
         let start = self.get_rook_position();
 
         // Looking at all directions (up, down, left, right):
-        Direction::all()
+        Direction::orthogonal()
             .iter()
-            .map(|d| {
-                match start
-                    // we look at the line in that direction
-                    .line(*d, self.size)
-                    .iter()
-                    .filter_map(|p| self.get_piece(p))
-                    // and get the first piece on the line:
-                    .next()
-                {
-                    // If there aren't any, then there
-                    // is no capture
-                    None => 0,
-                    Some(k) => match k {
-                        // If it's a bishop, no capture either
-                        PieceKind::Bishop => 0,
-                        // If it's a pawn, we capture it
-                        PieceKind::Pawn => 1,
-                        // If it's another rook, no capture
-                        PieceKind::Rook => 0,
-                    },
-                }
-            })
+            // we walk the line in that direction, capturing pieces until
+            // one blocks the rook...
+            .map(|d| self.rook_line(&start, *d, rules).len())
             // ... and we sum the number of captures.
             .sum()
     }
 
     /// Calculates and returns the total number of captures available
     /// for all the rooks on the board. If two rooks can capture the
-    /// same pawn, the capture is counted only once.
-    pub fn get_rooks_captures(&self) -> usize {
-        let rooks = self.get_rooks_positions();
-
-        rooks // For all rooks
-            .iter()
-            .map(|start| {
-                Direction::all()
-                    .iter()
-                    .filter_map(|d| {
-                        match start
-                            .line(*d, self.size)
-                            .iter()
-                            .filter_map(|p| self.get_piece(p).map(|k| (k, p)))
-                            .next()
-                        {
-                            None => None,
-                            Some((k, p)) => match k {
-                                PieceKind::Bishop => None,
-                                PieceKind::Pawn => Some(*p),
-                                PieceKind::Rook => None,
-                            },
-                        }
-                    })
-                    .collect::<HashSet<_>>()
-            })
-            .fold(HashSet::new(), |a, b| a.union(&b).copied().collect())
-            .len()
+    /// same piece, the capture is counted only once.
+    pub fn get_rooks_captures(&self, rules: &CaptureRules) -> usize {
+        let movement = PieceKind::Rook.movement().unwrap();
+        self.get_captures_with(PieceKind::Rook, &movement, rules)
     }
 
-    /// Computes the number of pawns the rook can capture in the
+    /// Computes the number of pieces the rook can capture in the
     /// board's current configuration, in parallel. This function
     /// assumes that there is only one rook on the board.
-    pub fn get_rook_captures_par(&self) -> usize {
+    pub fn get_rook_captures_par(&self, rules: &CaptureRules) -> usize {
         let start = self.get_rook_position();
 
-        Direction::all()
+        Direction::orthogonal()
             // We use a parallel iterator here
             .into_par_iter()
-            .map(|d| {
-                match start
-                    .line(d, self.size)
-                    .iter()
-                    .filter_map(|p| self.get_piece(p))
-                    .next()
-                {
-                    None => 0,
-                    Some(k) => match k {
-                        PieceKind::Bishop => 0,
-                        PieceKind::Pawn => 1,
-                        PieceKind::Rook => 0,
-                    },
-                }
-            })
+            .map(|d| self.rook_line(&start, d, rules).len())
             .sum()
     }
 
     /// Calculates and aggregates the number of captures for all the
-    /// rooks on the board. When two rooks can capture the same pawn,
+    /// rooks on the board. When two rooks can capture the same piece,
     /// only one capture is counted. The strategy here is to
     /// parallelize on the rooks and not on the 4 directions.
-    pub fn get_rooks_captures_par(&self) -> usize {
+    pub fn get_rooks_captures_par(&self, rules: &CaptureRules) -> usize {
         let rooks = self.get_rooks_positions();
 
         rooks
             .par_iter()
-            .map(|start| {
-                Direction::all()
-                    .iter()
-                    .filter_map(|d| {
-                        match start
-                            .line(*d, self.size)
-                            .iter()
-                            .filter_map(|p| self.get_piece(p).map(|k| (k, p)))
-                            .next()
-                        {
-                            None => None,
-                            Some((k, p)) => match k {
-                                PieceKind::Bishop => None,
-                                PieceKind::Pawn => Some(*p),
-                                PieceKind::Rook => None,
-                            },
-                        }
-                    })
+            .map(|start| self.rook_targets(start, rules).collect::<HashSet<_>>())
+            .reduce(HashSet::new, |a, b| a.union(&b).copied().collect())
+            .len()
+    }
+
+    /// Returns the pieces captured along a ray from a position
+    /// (excluded), following the rules. Rays of unlimited range
+    /// following one of the eight directions use the storage backend to
+    /// find the pieces, while the others only walk the squares in range.
+    fn ray_targets(&self, start: &Position, ray: &Ray, rules: &CaptureRules) -> Vec<Position> {
+        let (rows, cols) = ray.offset();
+        let range = ray.range().unwrap_or(u32::MAX);
+        let direction = Direction::from_vector((rows, cols)).filter(|_| ray.range().is_none());
+
+        // The number of steps from the start to a square of the ray
+        let steps = |p: &Position| match rows {
+            0 => p.col().abs_diff(start.col()) / cols.unsigned_abs(),
+            _ => p.row().abs_diff(start.row()) / rows.unsigned_abs(),
+        };
+
+        rules.walk(*start, |p| match direction {
+            Some(d) => self.first_piece(p, d),
+            None => std::iter::successors(p.offset(rows, cols, self.size), |q| {
+                q.offset(rows, cols, self.size)
+            })
+            .take_while(|q| steps(q) <= range)
+            .find_map(|q| self.get_piece(&q).map(|k| (k, q))),
+        })
+    }
+
+    /// Returns the pieces a piece with a given movement standing on a
+    /// position can capture along its rays, following the rules.
+    /// Move-only rays are ignored.
+    pub fn get_movement_targets(
+        &self,
+        movement: &Movement,
+        start: &Position,
+        rules: &CaptureRules,
+    ) -> Vec<Position> {
+        movement
+            .rays()
+            .iter()
+            .filter(|r| r.captures())
+            .flat_map(|r| self.ray_targets(start, r, rules))
+            .collect()
+    }
+
+    /// Returns the pieces a piece of a given kind standing on a position
+    /// can capture, following the movement the set gives the kind and
+    /// the rules. Pieces missing from the set capture nothing.
+    pub fn get_piece_targets(
+        &self,
+        kind: PieceKind,
+        start: &Position,
+        pieces: &PieceSet,
+        rules: &CaptureRules,
+    ) -> Vec<Position> {
+        match pieces.movement(kind) {
+            Some(movement) if rules.is_attacking(kind) => {
+                self.get_movement_targets(movement, start, rules)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Calculates the number of pieces the pieces of a given kind can
+    /// capture when they move with the given movement. A piece that can
+    /// be captured by several pieces is counted once.
+    pub fn get_captures_with(
+        &self,
+        attacker: PieceKind,
+        movement: &Movement,
+        rules: &CaptureRules,
+    ) -> usize {
+        if !rules.is_attacking(attacker) {
+            return 0;
+        }
+
+        self.pieces
+            .pieces()
+            .filter(|(k, _)| *k == attacker)
+            .flat_map(|(_, p)| self.get_movement_targets(movement, &p, rules))
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Calculates the number of pieces the pieces of a given kind can
+    /// capture, following the movement the set gives the kind. A piece
+    /// that can be captured by several pieces is counted once. Pieces
+    /// missing from the set capture nothing.
+    pub fn get_captures(
+        &self,
+        attacker: PieceKind,
+        pieces: &PieceSet,
+        rules: &CaptureRules,
+    ) -> usize {
+        match pieces.movement(attacker) {
+            Some(movement) => self.get_captures_with(attacker, movement, rules),
+            None => 0,
+        }
+    }
+
+    /// Calculates the number of pieces that can be captured by any
+    /// attacking piece on the board, whatever its kind, counting each
+    /// captured piece once. The movement of every kind of piece is taken
+    /// from a set, so that custom pieces attack as well, and pieces
+    /// missing from the set attack nothing. The attacking pieces are
+    /// considered in parallel, as rooks are in `get_rooks_captures_par`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, CaptureRules, PieceKind, PieceSet, Position};
+    /// let mut board = Board::new(8);
+    /// board.add_piece(PieceKind::Knight, Position::new(0, 0, 8));
+    /// board.add_piece(PieceKind::Bishop, Position::new(4, 4, 8));
+    /// board.add_pawn(Position::new(2, 1, 8));
+    /// board.add_pawn(Position::new(6, 6, 8));
+    /// board.add_pawn(Position::new(4, 7, 8));
+    ///
+    /// let pieces = PieceSet::new();
+    /// assert_eq!(board.get_all_captures_par(&pieces, &CaptureRules::new()), 2);
+    /// ```
+    pub fn get_all_captures_par(&self, pieces: &PieceSet, rules: &CaptureRules) -> usize {
+        let attackers = self
+            .pieces
+            .pieces()
+            .filter(|(k, _)| rules.is_attacking(*k))
+            .filter_map(|(k, p)| pieces.movement(k).map(|m| (m, p)))
+            .collect::<Vec<_>>();
+
+        attackers
+            .par_iter()
+            .map(|(m, p)| {
+                self.get_movement_targets(m, p, rules)
+                    .into_iter()
                     .collect::<HashSet<_>>()
             })
             .reduce(HashSet::new, |a, b| a.union(&b).copied().collect())
             .len()
     }
 
-    /// Prints the board on the console
+    /// Calculates the same number of captures as `get_rooks_captures_par`,
+    /// using the given strategy to count only once the pieces captured by
+    /// several rooks.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, CaptureRules, Deduplication};
+    /// let board: Board = "5\nR.p.R\n.....\np.B.p\n.....\nR.p.p\n".parse().unwrap();
+    /// let rules = CaptureRules::new();
+    ///
+    /// for deduplication in [
+    ///     Deduplication::HashSet,
+    ///     Deduplication::AtomicBitset,
+    ///     Deduplication::LocalBitsets,
+    /// ] {
+    ///     assert_eq!(board.get_rooks_captures_par_with(&rules, deduplication), 4);
+    /// }
+    /// ```
+    pub fn get_rooks_captures_par_with(
+        &self,
+        rules: &CaptureRules,
+        deduplication: Deduplication,
+    ) -> usize {
+        let squares = self.size as usize * self.size as usize;
+        let index = |p: Position| p.row() as usize * self.size as usize + p.col() as usize;
+
+        match deduplication {
+            Deduplication::HashSet => self.get_rooks_captures_par(rules),
+            Deduplication::AtomicBitset => {
+                let captured = AtomicBitSet::new(squares);
+
+                // Only the rook marking a piece first counts it
+                self.get_rooks_positions()
+                    .par_iter()
+                    .map(|start| {
+                        self.rook_targets(start, rules)
+                            .filter(|p| captured.insert(index(*p)))
+                            .count()
+                    })
+                    .sum()
+            }
+            Deduplication::LocalBitsets => self
+                .get_rooks_positions()
+                .par_iter()
+                .fold(
+                    || BitSet::new(squares),
+                    |mut captured, start| {
+                        self.rook_targets(start, rules)
+                            .for_each(|p| captured.insert(index(p)));
+                        captured
+                    },
+                )
+                .reduce(|| BitSet::new(squares), |a, b| a.union(&b))
+                .count(),
+        }
+    }
+
+    /// Calculates the same number of captures as `get_rooks_captures_par`,
+    /// by sweeping every row and every column once instead of walking
+    /// the lines of every rook: a piece can be captured if and only if
+    /// a rook sees it along its row or its column. The work only depends
+    /// on the size of the board, and not on the number of rooks. Rows,
+    /// then columns, are swept in parallel.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, CaptureRules, PieceKind};
+    /// let board: Board = "5\nR.p.R\n.....\np.B.p\n.....\nR.p.p\n".parse().unwrap();
+    ///
+    /// assert_eq!(board.get_rooks_captures_sweep(&CaptureRules::new()), 4);
+    ///
+    /// let rules = CaptureRules::new().blocking(PieceKind::Pawn, false);
+    /// assert_eq!(board.get_rooks_captures_sweep(&rules), 5);
+    /// ```
+    pub fn get_rooks_captures_sweep(&self, rules: &CaptureRules) -> usize {
+        let width = self.size as usize;
+
+        let mut squares = vec![None; width * width];
+        self.pieces
+            .pieces()
+            .for_each(|(k, p)| squares[p.row() as usize * width + p.col() as usize] = Some(k));
+
+        // Pieces captured along their row
+        let mut captured = vec![false; width * width];
+        captured
+            .par_chunks_mut(width.max(1))
+            .zip(squares.par_chunks(width.max(1)))
+            .for_each(|(captured, row)| {
+                let line = row
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, k)| k.map(|k| (i, k)));
+                line_captures(line, rules)
+                    .into_iter()
+                    .for_each(|i| captured[i] = true);
+            });
+
+        // Pieces captured along their column, but not along their row
+        let vertical: usize = (0..width)
+            .into_par_iter()
+            .map(|col| {
+                let line =
+                    (0..width).filter_map(|row| squares[row * width + col].map(|k| (row, k)));
+                line_captures(line, rules)
+                    .into_iter()
+                    .filter(|row| !captured[row * width + col])
+                    .count()
+            })
+            .sum();
+
+        captured.par_iter().filter(|c| **c).count() + vertical
+    }
+
+    /// Returns true if a piece with a given movement standing on a
+    /// position can go to another one along one of its rays, every
+    /// square in between being empty. Only the rays used to capture are
+    /// considered when `capture` is true, and only the ones used to move
+    /// otherwise.
+    fn reaches(&self, movement: &Movement, from: &Position, to: &Position, capture: bool) -> bool {
+        // The number of times an offset is repeated to go to the destination
+        let steps = |(rows, cols): (i32, i32), p: &Position| {
+            let dr = p.row() as i64 - from.row() as i64;
+            let dc = p.col() as i64 - from.col() as i64;
+            let k = match rows {
+                0 => dc / cols as i64,
+                _ => dr / rows as i64,
+            };
+
+            if k >= 1 && dr == k * rows as i64 && dc == k * cols as i64 {
+                Some(k as u32)
+            } else {
+                None
+            }
+        };
+
+        movement
+            .rays()
+            .iter()
+            .filter(|r| if capture { r.captures() } else { r.moves() })
+            .any(|r| {
+                let (rows, cols) = r.offset();
+                let k = match steps((rows, cols), to) {
+                    Some(k) if k <= r.range().unwrap_or(u32::MAX) => k,
+                    _ => return false,
+                };
+
+                match Direction::from_vector((rows, cols)) {
+                    Some(d) => self
+                        .first_piece(from, d)
+                        .and_then(|(_, p)| steps((rows, cols), &p))
+                        .is_none_or(|first| first >= k),
+                    None => (1..k as i32).all(|i| {
+                        from.offset(rows * i, cols * i, self.size)
+                            .is_some_and(|p| self.get_piece(&p).is_none())
+                    }),
+                }
+            })
+    }
+
+    /// Moves the piece standing on a square to another one, capturing
+    /// the piece standing there, if any. The move must follow the
+    /// movement the set gives the piece, and a capture must be allowed
+    /// by the rules. The move is recorded so that it can be undone, and
+    /// the moves undone so far can't be redone anymore.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, CaptureRules, MoveError, Movement, PieceKind, PieceSet, Position};
+    /// let rules = CaptureRules::new();
+    /// let mut pieces = PieceSet::new();
+    /// let camel = pieces.register("camel", Movement::leaper((1, 3)));
+    ///
+    /// let mut board = Board::new(8);
+    /// board.add_rook(Position::new(0, 0, 8));
+    /// board.add_pawn(Position::new(0, 5, 8));
+    /// board.add_piece(camel, Position::new(4, 4, 8));
+    ///
+    /// assert_eq!(
+    ///     board.move_piece(&Position::new(0, 0, 8), Position::new(1, 1, 8), &pieces, &rules),
+    ///     Err(MoveError::Unreachable)
+    /// );
+    ///
+    /// let played = board.move_piece(&Position::new(0, 0, 8), Position::new(0, 5, 8), &pieces, &rules);
+    /// assert_eq!(played.unwrap().captured, Some(PieceKind::Pawn));
+    /// assert_eq!(board.pieces().count(), 2);
+    ///
+    /// board.move_piece(&Position::new(4, 4, 8), Position::new(5, 7, 8), &pieces, &rules).unwrap();
+    /// assert_eq!(board.get_piece(&Position::new(5, 7, 8)), Some(camel));
+    /// board.undo();
+    /// assert_eq!(board.get_piece(&Position::new(4, 4, 8)), Some(camel));
+    ///
+    /// board.undo();
+    /// assert_eq!(board.get_piece(&Position::new(0, 5, 8)), Some(PieceKind::Pawn));
+    /// board.redo();
+    /// assert_eq!(board.get_piece(&Position::new(0, 5, 8)), Some(PieceKind::Rook));
+    /// ```
+    pub fn move_piece(
+        &mut self,
+        from: &Position,
+        to: Position,
+        pieces: &PieceSet,
+        rules: &CaptureRules,
+    ) -> Result<Move, MoveError> {
+        let piece = self.get_piece(from).ok_or(MoveError::NoPiece)?;
+        let captured = self.get_piece(&to);
+        let movement = pieces.movement(piece).ok_or(MoveError::UnknownPiece)?;
+
+        match captured {
+            Some(target) if !rules.is_attacking(piece) || !rules.is_capturable(target) => {
+                return Err(MoveError::Uncapturable)
+            }
+            _ if !self.reaches(movement, from, &to, captured.is_some()) => {
+                return Err(MoveError::Unreachable)
+            }
+            _ => (),
+        }
+
+        let played = Move {
+            piece,
+            from: *from,
+            to,
+            captured,
+        };
+        self.play(&played);
+        self.history.push(played);
+        self.future.clear();
+
+        Ok(played)
+    }
+
+    fn play(&mut self, played: &Move) {
+        self.pieces.remove(&played.from);
+        self.pieces.remove(&played.to);
+        self.pieces.insert(played.piece, played.to);
+    }
+
+    /// Takes back the last move played, putting back the piece it
+    /// captured, and returns it.
+    pub fn undo(&mut self) -> Option<Move> {
+        let played = self.history.pop()?;
+
+        self.pieces.remove(&played.to);
+        self.pieces.insert(played.piece, played.from);
+        if let Some(captured) = played.captured {
+            self.pieces.insert(captured, played.to);
+        }
+
+        self.future.push(played);
+        Some(played)
+    }
+
+    /// Plays again the last move undone, and returns it.
+    pub fn redo(&mut self) -> Option<Move> {
+        let played = self.future.pop()?;
+
+        self.play(&played);
+        self.history.push(played);
+        Some(played)
+    }
+
+    /// Returns the moves played, the first one first. Adding or removing
+    /// pieces outside of moves clears the history.
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// Prints the board on the console, without its size
     pub fn print(&self) {
-        (0..self.size).for_each(|row| {
-            let s = (0..self.size)
-                .map(|col| {
-                    let kind = self.get_piece(&Position::new(row, col, self.size));
-
-                    match kind {
-                        None => '.',
-                        Some(k) => match k {
-                            PieceKind::Rook => 'R',
-                            PieceKind::Pawn => 'p',
-                            PieceKind::Bishop => 'B',
-                        },
-                    }
-                })
-                .collect::<String>();
-            println!("{}", s);
-        })
+        self.to_string()
+            .lines()
+            .skip(1)
+            .for_each(|row| println!("{}", row));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Board, CaptureRules, Deduplication, PieceKind, Position};
+
+    /// Rooks in three corners, two of them capturing the same pawns
+    const CORNERS: &str = "5\nR.p.R\n.....\np.B.p\n.....\nR.p.p\n";
+
+    const DEDUPLICATIONS: [Deduplication; 3] = [
+        Deduplication::HashSet,
+        Deduplication::AtomicBitset,
+        Deduplication::LocalBitsets,
+    ];
+
+    fn board(text: &str) -> Board {
+        text.parse().unwrap()
+    }
+
+    /// Checks every deduplication strategy against the expected count.
+    fn check_par_with(board: &Board, rules: &CaptureRules, captures: usize) {
+        DEDUPLICATIONS.iter().for_each(|d| {
+            assert_eq!(
+                board.get_rooks_captures_par_with(rules, *d),
+                captures,
+                "{:?}",
+                d
+            );
+        });
+    }
+
+    #[test]
+    fn sweep_counts_shared_pieces_once() {
+        let board = board(CORNERS);
+        assert_eq!(board.get_rooks_captures_sweep(&CaptureRules::new()), 4);
+
+        let rules = CaptureRules::new().blocking(PieceKind::Pawn, false);
+        assert_eq!(board.get_rooks_captures_sweep(&rules), 5);
+    }
+
+    #[test]
+    fn sweep_piece_seen_along_its_row_and_column() {
+        let board = board("3\nR.p\n...\n..R\n");
+        assert_eq!(board.get_rooks_captures_sweep(&CaptureRules::new()), 1);
+    }
+
+    #[test]
+    fn sweep_adjacent_pieces() {
+        let board = board("2\nRp\npR\n");
+        assert_eq!(board.get_rooks_captures_sweep(&CaptureRules::new()), 2);
+    }
+
+    #[test]
+    fn sweep_board_edges() {
+        let mut board = Board::new(8);
+        board.add_rook(Position::new(0, 0, 8));
+        board.add_pawn(Position::new(0, 7, 8));
+        board.add_pawn(Position::new(7, 0, 8));
+        board.add_pawn(Position::new(7, 7, 8));
+
+        assert_eq!(board.get_rooks_captures_sweep(&CaptureRules::new()), 2);
+    }
+
+    #[test]
+    fn sweep_empty_boards() {
+        let rules = CaptureRules::new();
+        assert_eq!(Board::new(8).get_rooks_captures_sweep(&rules), 0);
+        assert_eq!(board("1\nR\n").get_rooks_captures_sweep(&rules), 0);
+    }
+
+    #[test]
+    fn sweep_rooks_not_attacking() {
+        let rules = CaptureRules::new().attacking(PieceKind::Rook, false);
+        assert_eq!(board(CORNERS).get_rooks_captures_sweep(&rules), 0);
+    }
+
+    #[test]
+    fn par_with_counts_shared_pieces_once() {
+        let board = board(CORNERS);
+        check_par_with(&board, &CaptureRules::new(), 4);
+
+        let rules = CaptureRules::new().blocking(PieceKind::Pawn, false);
+        check_par_with(&board, &rules, 5);
+    }
+
+    #[test]
+    fn par_with_adjacent_pieces() {
+        check_par_with(&board("2\nRp\npR\n"), &CaptureRules::new(), 2);
+    }
+
+    #[test]
+    fn par_with_last_square_past_a_word() {
+        // The 81st square lies in the second word of the bitsets
+        let mut board = Board::new(9);
+        board.add_rook(Position::new(0, 8, 9));
+        board.add_rook(Position::new(8, 0, 9));
+        board.add_pawn(Position::new(8, 8, 9));
+
+        check_par_with(&board, &CaptureRules::new(), 1);
+    }
+
+    #[test]
+    fn par_with_empty_boards() {
+        let rules = CaptureRules::new();
+        check_par_with(&Board::new(8), &rules, 0);
+        check_par_with(&board("1\nR\n"), &rules, 0);
     }
 }
@@ -0,0 +1,221 @@
+use crate::{Board, BoardStorage, ParseError, ParseErrorKind, PieceKind, Position};
+
+/// The size of the boards FEN describes.
+const SIZE: u32 = 8;
+
+/// Reads the pieces of a board of a given size from its rows, separated
+/// by `/`, the first row first. A row holds pieces, as letters of any
+/// case, and runs of empty squares, as numbers. Custom pieces, as their
+/// id between parentheses, are only read if `custom` is true. `column`
+/// is the column of the first character of the rows, for the errors.
+fn parse_placement(
+    text: &str,
+    size: u32,
+    column: usize,
+    custom: bool,
+) -> Result<Vec<(PieceKind, Position)>, ParseError> {
+    let error = |i: usize, kind| Err(ParseError::new(1, column + i, kind));
+    let chars = text.chars().collect::<Vec<_>>();
+
+    let mut pieces = Vec::new();
+    let (mut row, mut col) = (0, 0u64);
+    let mut i = 0;
+    while i < chars.len() {
+        let kind = match chars[i] {
+            '/' if col != size as u64 => return error(i, ParseErrorKind::RaggedRow(col as usize)),
+            '/' if row + 1 == size => return error(i, ParseErrorKind::TooManyRows),
+            '/' => {
+                row += 1;
+                col = 0;
+                i += 1;
+                continue;
+            }
+            '1'..='9' => {
+                let digits = chars[i..].iter().take_while(|c| c.is_ascii_digit());
+                let run = digits.clone().fold(0u64, |n, c| {
+                    n.saturating_mul(10)
+                        .saturating_add(c.to_digit(10).unwrap() as u64)
+                });
+                i += digits.count();
+                col = col.saturating_add(run);
+                continue;
+            }
+            '(' if custom => {
+                let id = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| **c != ')')
+                    .collect::<String>();
+                let closed = i + 1 + id.chars().count() < chars.len();
+
+                // Only the shortest writing of an id is read
+                match id.parse::<u8>() {
+                    Ok(kind) if closed && kind.to_string() == id => {
+                        i += id.chars().count() + 2;
+                        PieceKind::Custom(kind)
+                    }
+                    _ => return error(i, ParseErrorKind::InvalidCustomKind),
+                }
+            }
+            c => {
+                let kind = match c.to_ascii_lowercase() {
+                    'p' => PieceKind::Pawn,
+                    'r' => PieceKind::Rook,
+                    'b' => PieceKind::Bishop,
+                    'q' => PieceKind::Queen,
+                    'k' => PieceKind::King,
+                    'n' => PieceKind::Knight,
+                    _ => return error(i, ParseErrorKind::UnknownGlyph(c)),
+                };
+                i += 1;
+                kind
+            }
+        };
+
+        if col < size as u64 {
+            pieces.push((kind, Position::new(row, col as u32, size)));
+        }
+        col += 1;
+    }
+
+    if col != size as u64 {
+        return error(chars.len(), ParseErrorKind::RaggedRow(col as usize));
+    }
+    if row + 1 < size {
+        return error(chars.len(), ParseErrorKind::MissingRows);
+    }
+
+    Ok(pieces)
+}
+
+impl Board {
+    /// Recreates an 8x8 board from a FEN string. Only the piece
+    /// placement, up to the first space, is read: the rest of the
+    /// string is ignored. Colours are ignored as well, and a letter
+    /// stands for the same kind of piece whatever its case. Errors are
+    /// on line 1, at the column of the offending character.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, CaptureRules};
+    /// let rules = CaptureRules::new();
+    ///
+    /// let example1 = "4P3/5P2/2P1r3/6P1/4b3/4P3/8/8";
+    /// let board = Board::from_fen(example1).unwrap();
+    /// assert_eq!(board.get_rooks_captures(&rules), 2);
+    /// assert_eq!(board.to_fen().unwrap(), example1);
+    ///
+    /// let example2 = "1b6/4Pr1P/8/1r3P2/8/4r1bP/2b5/4P2b";
+    /// let board = Board::from_fen(example2).unwrap();
+    /// assert_eq!(board.get_rooks_captures(&rules), 4);
+    /// assert_eq!(board.to_fen().unwrap(), example2);
+    /// ```
+    pub fn from_fen(fen: &str) -> Result<Board, ParseError> {
+        let placement = fen.split(' ').next().unwrap_or("");
+        let pieces = parse_placement(placement, SIZE, 1, false)?;
+
+        let mut board = Board::with_capacity(SIZE, pieces.len());
+        board.set_pieces(pieces);
+        Ok(board)
+    }
+
+    /// Recreates a board of any size from the notation written by
+    /// `to_extended_fen`: the size of the board and a colon, then its
+    /// rows as in FEN, where runs of empty squares may take several
+    /// digits, and custom pieces are written as their id between
+    /// parentheses. Errors are on line 1, at the column of the
+    /// offending character.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, ParseErrorKind, PieceKind, Position};
+    /// let text = "12:12/3P2r(7)4/12/12/12/12/12/12/12/12/12/12";
+    /// let board = Board::from_extended_fen(text).unwrap();
+    /// assert_eq!(
+    ///     board.get_piece(&Position::new(1, 7, 12)),
+    ///     Some(PieceKind::Custom(7))
+    /// );
+    /// assert_eq!(board.to_extended_fen(), text);
+    ///
+    /// let error = Board::from_extended_fen("12:12/10/12").err().unwrap();
+    /// assert_eq!(error.column(), 9);
+    /// assert_eq!(error.kind(), ParseErrorKind::RaggedRow(10));
+    /// ```
+    pub fn from_extended_fen(text: &str) -> Result<Board, ParseError> {
+        let text = text.trim_end();
+        let invalid = ParseError::new(1, 1, ParseErrorKind::InvalidSize);
+
+        let (header, placement) = text.split_once(':').ok_or(invalid)?;
+        let size = header.parse::<u32>().map_err(|_| invalid)?;
+        let pieces = parse_placement(placement, size, header.chars().count() + 2, true)?;
+
+        let mut board = Board::with_capacity(size, pieces.len());
+        board.set_pieces(pieces);
+        Ok(board)
+    }
+}
+
+impl<S: BoardStorage> Board<S> {
+    /// Writes the rows of the board, separated by `/`, the first row
+    /// first, with runs of empty squares as numbers. Pawns are written
+    /// as white pieces and the other pieces as black ones. Returns
+    /// `None` if the board holds custom pieces, unless `custom` is true.
+    fn write_placement(&self, custom: bool) -> Option<String> {
+        let mut pieces = self.pieces().collect::<Vec<_>>();
+        pieces.sort_by_key(|(_, p)| (p.row(), p.col()));
+        let mut pieces = pieces.into_iter().peekable();
+
+        let mut rows = Vec::with_capacity(self.size() as usize);
+        for row in 0..self.size() {
+            let mut text = String::new();
+            let mut col = 0;
+
+            while let Some((kind, p)) = pieces.next_if(|(_, p)| p.row() == row) {
+                if p.col() > col {
+                    text.push_str(&(p.col() - col).to_string());
+                }
+                match kind {
+                    PieceKind::Pawn => text.push('P'),
+                    PieceKind::Rook => text.push('r'),
+                    PieceKind::Bishop => text.push('b'),
+                    PieceKind::Queen => text.push('q'),
+                    PieceKind::King => text.push('k'),
+                    PieceKind::Knight => text.push('n'),
+                    PieceKind::Custom(id) if custom => text.push_str(&format!("({})", id)),
+                    PieceKind::Custom(_) => return None,
+                }
+                col = p.col() + 1;
+            }
+            if self.size() > col {
+                text.push_str(&(self.size() - col).to_string());
+            }
+
+            rows.push(text);
+        }
+
+        Some(rows.join("/"))
+    }
+
+    /// Writes the pieces of an 8x8 board as the piece placement of a FEN
+    /// string, the first row being the 8th rank. As in the original
+    /// problem, pawns are written as white pieces and the other pieces
+    /// as black ones. Returns `None` if the board is not 8x8, or if it
+    /// holds custom pieces, which FEN can't describe.
+    pub fn to_fen(&self) -> Option<String> {
+        if self.size() != SIZE {
+            return None;
+        }
+
+        self.write_placement(false)
+    }
+
+    /// Writes the board in a notation extending FEN to boards of any
+    /// size, which `Board::from_extended_fen` reads back: the size of
+    /// the board and a colon, then the rows as `to_fen` writes them,
+    /// custom pieces being written as their id between parentheses. On
+    /// sparse boards, this is much shorter than one character per
+    /// square.
+    pub fn to_extended_fen(&self) -> String {
+        let placement = self.write_placement(true).unwrap();
+        format!("{}:{}", self.size(), placement)
+    }
+}
@@ -0,0 +1,436 @@
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::{Board, BoardStorage, CaptureRules, Direction, PieceKind, Position};
+
+/// Adds to every square of a line the number of rooks attacking it
+/// along the line, from either side. A rook attacks every square up to
+/// and including the first blocking piece.
+fn line_heat(line: &[Option<PieceKind>], rules: &CaptureRules, heat: &mut [u32]) {
+    let attacks = rules.is_attacking(PieceKind::Rook);
+
+    let mut sweep = |order: &mut dyn Iterator<Item = usize>| {
+        let mut rooks = 0;
+        order.for_each(|i| {
+            heat[i] += rooks;
+            if let Some(k) = line[i] {
+                if rules.is_blocking(k) {
+                    rooks = 0;
+                }
+                if attacks && k == PieceKind::Rook {
+                    rooks += 1;
+                }
+            }
+        });
+    };
+    sweep(&mut (0..line.len()));
+    sweep(&mut (0..line.len()).rev());
+}
+
+/// The number of rooks attacking every square of a board.
+#[derive(Debug, Clone)]
+pub struct Heatmap {
+    size: u32,
+    /// Row after row
+    counts: Vec<u32>,
+}
+
+impl Heatmap {
+    /// Returns the size of the board.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the number of rooks attacking a square.
+    pub fn get(&self, position: &Position) -> u32 {
+        self.counts[position.row() as usize * self.size as usize + position.col() as usize]
+    }
+
+    /// Returns the squares attacked by at least `min` rooks, along with
+    /// their number of attackers.
+    pub fn contested(&self, min: u32) -> impl Iterator<Item = (Position, u32)> + '_ {
+        let size = self.size;
+
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(move |(_, c)| **c >= min.max(1))
+            .map(move |(i, c)| {
+                let position = Position::new(i as u32 / size, i as u32 % size, size);
+                (position, *c)
+            })
+    }
+}
+
+/// Turns runs of attacked coordinates along a line, given as their
+/// first and last coordinates, into the coordinates where the number of
+/// runs covering the line changes, along with the number from there on.
+fn overlap(runs: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    let mut changes = runs
+        .into_iter()
+        .flat_map(|(first, last)| vec![(first, 1), (last + 1, -1)])
+        .collect::<Vec<(u32, i64)>>();
+    changes.sort_unstable();
+
+    let mut counts: Vec<(u32, u32)> = Vec::new();
+    let mut count = 0;
+    changes.into_iter().for_each(|(coord, change)| {
+        count += change;
+        match counts.last_mut() {
+            Some(last) if last.0 == coord => last.1 = count as u32,
+            _ => counts.push((coord, count as u32)),
+        }
+    });
+
+    counts
+}
+
+/// For every line holding attacked squares, the coordinates where the
+/// number of attackers changes, in order, along with the number from
+/// there on. The number drops back to zero after the last attacked
+/// square.
+type Runs = HashMap<u32, Vec<(u32, u32)>>;
+
+/// Returns the number of attackers of a coordinate along a line.
+fn count(runs: &Runs, line: u32, coord: u32) -> u32 {
+    runs.get(&line).map_or(0, |counts| {
+        match counts.partition_point(|(c, _)| *c <= coord) {
+            0 => 0,
+            i => counts[i - 1].1,
+        }
+    })
+}
+
+/// Returns the squares covered by the runs of a family of lines, along
+/// the rows or along the columns.
+fn attacked(runs: &Runs, size: u32, along_rows: bool) -> impl Iterator<Item = Position> + '_ {
+    runs.iter().flat_map(move |(&line, counts)| {
+        counts
+            .windows(2)
+            .filter(|w| w[0].1 > 0)
+            .flat_map(|w| w[0].0..w[1].0)
+            .map(move |coord| match along_rows {
+                true => Position::new(line, coord, size),
+                false => Position::new(coord, line, size),
+            })
+    })
+}
+
+/// The number of rooks attacking every square of a board, kept as runs
+/// of squares along the rows and the columns rather than square by
+/// square.
+#[derive(Debug, Clone)]
+pub struct AttackCounts {
+    size: u32,
+    /// Keyed by row, along the columns
+    rows: Runs,
+    /// Keyed by column, along the rows
+    columns: Runs,
+}
+
+impl AttackCounts {
+    /// Returns the size of the board.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the number of rooks attacking a square, in logarithmic
+    /// time.
+    pub fn get(&self, position: &Position) -> u32 {
+        let (row, col) = (position.row(), position.col());
+        count(&self.rows, row, col) + count(&self.columns, col, row)
+    }
+
+    /// Returns the squares attacked by at least `min` rooks, along with
+    /// their number of attackers, in no particular order.
+    pub fn contested(&self, min: u32) -> impl Iterator<Item = (Position, u32)> + '_ {
+        // Squares attacked along their column are only listed here when
+        // no rook attacks them along their row
+        attacked(&self.rows, self.size, true)
+            .chain(
+                attacked(&self.columns, self.size, false)
+                    .filter(move |p| count(&self.rows, p.row(), p.col()) == 0),
+            )
+            .map(move |p| (p, self.get(&p)))
+            .filter(move |(_, c)| *c >= min.max(1))
+    }
+}
+
+impl<S: BoardStorage> Board<S> {
+    /// Counts, for every square of the board, the rooks attacking it
+    /// along its row and its column, through the pieces that don't
+    /// block. Every row, then every column, is swept once in parallel,
+    /// so the work only depends on the size of the board. The grid
+    /// takes one counter per square: on huge sparse boards, prefer
+    /// `get_attack_counts`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, CaptureRules, Position};
+    /// let mut board = Board::new(8);
+    /// board.add_rook(Position::new(0, 2, 8));
+    /// board.add_rook(Position::new(4, 6, 8));
+    /// board.add_pawn(Position::new(4, 2, 8));
+    ///
+    /// let heatmap = board.get_attack_heatmap(&CaptureRules::new());
+    ///
+    /// assert_eq!(heatmap.get(&Position::new(4, 2, 8)), 2);
+    /// assert_eq!(heatmap.get(&Position::new(0, 6, 8)), 2);
+    /// assert_eq!(heatmap.get(&Position::new(5, 2, 8)), 0);
+    /// ```
+    pub fn get_attack_heatmap(&self, rules: &CaptureRules) -> Heatmap {
+        let width = self.size() as usize;
+
+        let mut squares = vec![None; width * width];
+        self.pieces()
+            .for_each(|(k, p)| squares[p.row() as usize * width + p.col() as usize] = Some(k));
+
+        // Attacks along the rows
+        let mut counts = vec![0; width * width];
+        counts
+            .par_chunks_mut(width.max(1))
+            .zip(squares.par_chunks(width.max(1)))
+            .for_each(|(heat, row)| line_heat(row, rules, heat));
+
+        // Attacks along the columns, stored column after column
+        let mut vertical = vec![0; width * width];
+        vertical
+            .par_chunks_mut(width.max(1))
+            .enumerate()
+            .for_each(|(col, heat)| {
+                let column = (0..width)
+                    .map(|row| squares[row * width + col])
+                    .collect::<Vec<_>>();
+                line_heat(&column, rules, heat);
+            });
+
+        counts
+            .par_chunks_mut(width.max(1))
+            .enumerate()
+            .for_each(|(row, heat)| {
+                heat.iter_mut()
+                    .enumerate()
+                    .for_each(|(col, h)| *h += vertical[col * width + row]);
+            });
+
+        Heatmap {
+            size: self.size(),
+            counts,
+        }
+    }
+
+    /// Counts the rooks attacking every square, as `get_attack_heatmap`
+    /// does, but keeps the squares each rook attacks as a run along its
+    /// row or its column, up to and including the first blocking piece.
+    /// The lines of the rooks are walked in parallel from piece to
+    /// piece, so with a storage finding the first piece quickly, such as
+    /// `SparseStorage`, the time and the memory used only depend on the
+    /// number of pieces, and not on the size of the board.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, CaptureRules, Position, SparseStorage};
+    /// let size = 1_000_000;
+    /// let mut board = Board::<SparseStorage>::with_storage(size);
+    /// board.add_rook(Position::new(0, 2, size));
+    /// board.add_rook(Position::new(4, 6, size));
+    /// board.add_pawn(Position::new(4, 2, size));
+    ///
+    /// let counts = board.get_attack_counts(&CaptureRules::new());
+    ///
+    /// assert_eq!(counts.get(&Position::new(4, 2, size)), 2);
+    /// assert_eq!(counts.get(&Position::new(0, 6, size)), 2);
+    /// assert_eq!(counts.get(&Position::new(5, 2, size)), 0);
+    /// assert_eq!(counts.get(&Position::new(4, 999_999, size)), 1);
+    /// ```
+    pub fn get_attack_counts(&self, rules: &CaptureRules) -> AttackCounts {
+        let size = self.size();
+        let last = size.saturating_sub(1);
+        let attacks = rules.is_attacking(PieceKind::Rook);
+
+        // The squares attacked from every rook along every direction, as
+        // the direction, the row or column, and the first and last
+        // coordinates along it
+        let runs = self
+            .get_rooks_positions()
+            .par_iter()
+            .filter(|_| attacks)
+            .flat_map_iter(|start| {
+                Direction::orthogonal().into_iter().filter_map(move |d| {
+                    let (row, col) = (start.row(), start.col());
+                    let end = match rules.sight(*start, |p| self.first_piece(p, d)).last() {
+                        Some((k, p)) if rules.is_blocking(*k) => Some(*p),
+                        _ => None,
+                    };
+
+                    match d {
+                        Direction::East if col < last => {
+                            Some((d, row, col + 1, end.map_or(last, |p| p.col())))
+                        }
+                        Direction::West if col > 0 => {
+                            Some((d, row, end.map_or(0, |p| p.col()), col - 1))
+                        }
+                        Direction::South if row < last => {
+                            Some((d, col, row + 1, end.map_or(last, |p| p.row())))
+                        }
+                        Direction::North if row > 0 => {
+                            Some((d, col, end.map_or(0, |p| p.row()), row - 1))
+                        }
+                        _ => None,
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let (rows, columns): (Vec<_>, Vec<_>) = runs
+            .into_iter()
+            .partition(|(d, ..)| matches!(d, Direction::East | Direction::West));
+        let lines = |runs: Vec<(Direction, u32, u32, u32)>| {
+            let mut lines = HashMap::<u32, Vec<(u32, u32)>>::new();
+            runs.into_iter().for_each(|(_, line, first, last)| {
+                lines.entry(line).or_default().push((first, last))
+            });
+            lines
+                .into_par_iter()
+                .map(|(line, runs)| (line, overlap(runs)))
+                .collect()
+        };
+
+        AttackCounts {
+            size,
+            rows: lines(rows),
+            columns: lines(columns),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Board, CaptureRules, PieceKind, Position};
+
+    fn board(size: u32, pieces: &[(PieceKind, u32, u32)]) -> Board {
+        let mut board = Board::new(size);
+        pieces
+            .iter()
+            .for_each(|(k, row, col)| board.add_piece(*k, Position::new(*row, *col, size)));
+        board
+    }
+
+    /// Checks the heatmap and the attack counts against the number of
+    /// attackers of every square, the squares left out having none.
+    fn check(board: &Board, rules: &CaptureRules, expected: &[(u32, u32, u32)]) {
+        let size = board.size();
+        let heatmap = board.get_attack_heatmap(rules);
+        let counts = board.get_attack_counts(rules);
+
+        for row in 0..size {
+            for col in 0..size {
+                let position = Position::new(row, col, size);
+                let attackers = expected
+                    .iter()
+                    .find(|(r, c, _)| (*r, *c) == (row, col))
+                    .map_or(0, |(_, _, n)| *n);
+
+                assert_eq!(heatmap.get(&position), attackers, "{:?}", position);
+                assert_eq!(counts.get(&position), attackers, "{:?}", position);
+            }
+        }
+        assert_eq!(heatmap.contested(1).count(), expected.len());
+        assert_eq!(counts.contested(1).count(), expected.len());
+    }
+
+    #[test]
+    fn empty_board() {
+        check(&board(8, &[]), &CaptureRules::new(), &[]);
+    }
+
+    #[test]
+    fn single_square() {
+        check(
+            &board(1, &[(PieceKind::Rook, 0, 0)]),
+            &CaptureRules::new(),
+            &[],
+        );
+    }
+
+    #[test]
+    fn rook_in_corner() {
+        let board = board(4, &[(PieceKind::Rook, 0, 0)]);
+        let expected = [
+            (0, 1, 1),
+            (0, 2, 1),
+            (0, 3, 1),
+            (1, 0, 1),
+            (2, 0, 1),
+            (3, 0, 1),
+        ];
+
+        check(&board, &CaptureRules::new(), &expected);
+    }
+
+    #[test]
+    fn adjacent_pieces() {
+        let board = board(
+            4,
+            &[
+                (PieceKind::Rook, 1, 1),
+                (PieceKind::Pawn, 1, 2),
+                (PieceKind::Rook, 1, 3),
+            ],
+        );
+        let expected = [
+            (1, 0, 1),
+            (1, 2, 2),
+            (0, 1, 1),
+            (2, 1, 1),
+            (3, 1, 1),
+            (0, 3, 1),
+            (2, 3, 1),
+            (3, 3, 1),
+        ];
+
+        check(&board, &CaptureRules::new(), &expected);
+        assert_eq!(
+            board
+                .get_attack_counts(&CaptureRules::new())
+                .contested(2)
+                .collect::<Vec<_>>(),
+            vec![(Position::new(1, 2, 4), 2)]
+        );
+    }
+
+    #[test]
+    fn pieces_seen_through() {
+        let board = board(
+            4,
+            &[
+                (PieceKind::Rook, 1, 1),
+                (PieceKind::Pawn, 1, 2),
+                (PieceKind::Rook, 1, 3),
+            ],
+        );
+        let rules = CaptureRules::new().blocking(PieceKind::Pawn, false);
+        let expected = [
+            (1, 0, 1),
+            (1, 1, 1),
+            (1, 2, 2),
+            (1, 3, 1),
+            (0, 1, 1),
+            (2, 1, 1),
+            (3, 1, 1),
+            (0, 3, 1),
+            (2, 3, 1),
+            (3, 3, 1),
+        ];
+
+        check(&board, &rules, &expected);
+    }
+
+    #[test]
+    fn rooks_not_attacking() {
+        let board = board(4, &[(PieceKind::Rook, 1, 1), (PieceKind::Pawn, 1, 3)]);
+        let rules = CaptureRules::new().attacking(PieceKind::Rook, false);
+
+        check(&board, &rules, &[]);
+    }
+}
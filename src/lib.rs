@@ -6,8 +6,62 @@ pub use utils::{Direction, Position};
 mod piece;
 pub use piece::PieceKind;
 
+mod movement;
+pub use movement::{Mode, Movement, PieceSet, Ray};
+
+mod betza;
+pub use betza::{BetzaError, BetzaErrorKind};
+
+mod rules;
+pub use rules::CaptureRules;
+
+mod storage;
+pub use storage::{BoardStorage, GridStorage, ListStorage};
+
+mod bitboard;
+pub use bitboard::BitStorage;
+
+mod sparse;
+pub use sparse::SparseStorage;
+
+mod bitset;
+
+mod moves;
+pub use moves::{Move, MoveError};
+
 mod board;
-pub use board::Board;
+pub use board::{Board, Deduplication};
+
+mod parse;
+pub use parse::{ParseError, ParseErrorKind};
+
+mod fen;
+
+mod report;
+pub use report::{Capture, CaptureReport};
+
+mod heatmap;
+pub use heatmap::{AttackCounts, Heatmap};
+
+mod tracked;
+pub use tracked::TrackedBoard;
+
+mod tour;
+pub use tour::CaptureTour;
+
+mod placement;
+pub use placement::Placement;
+
+mod stream;
+pub use stream::CaptureStream;
+
+mod index;
+pub use index::BoardIndex;
 
 mod bench;
-pub use bench::{Benchmark, BoardGenerator, Generator};
+pub use bench::{Benchmark, BenchmarkResult, BoardGenerator, Generator};
+
+#[cfg(feature = "serde")]
+mod schema;
+#[cfg(feature = "serde")]
+pub use schema::SCHEMA_VERSION;
@@ -1,7 +1,13 @@
 extern crate rayon_logs as rayon;
 
 use chess::Generator;
-use chess::{Benchmark, Board, BoardGenerator};
+use chess::{
+    Benchmark, BitStorage, Board, BoardGenerator, BoardIndex, CaptureRules, Deduplication,
+};
+
+/// The same generated board, in the list and bitset backends, and its
+/// index.
+type Boards = (Board, Board<BitStorage>, BoardIndex);
 
 fn generate(generator: &BoardGenerator) -> Board {
     println!("Generating...");
@@ -22,31 +28,72 @@ pub fn main() {
 
     pool.compare()
         .runs_number(20)
-        .attach_algorithm_with_setup("seq", || generate(&generator), |g| g.get_rooks_captures())
+        .attach_algorithm_with_setup("seq", || generate(&generator), |g| g.get_rooks_captures(&CaptureRules::new()))
         .attach_algorithm_with_setup(
             "par",
             || generate(&generator),
-            |g| g.get_rooks_captures_par(),
+            |g| g.get_rooks_captures_par(&CaptureRules::new()),
         )
         .generate_logs("log.html")
         .unwrap();
      */
 
-    let results = Benchmark::new()
+    let _results = Benchmark::new()
         .threads(vec![1, 2, 3, 4])
         .sizes(vec![256, 512])
         .runs(2)
         .add_function(
-            Box::new(|b: &Board| {
-                b.get_rook_captures_par();
+            Box::new(|(b, _, _): &Boards| {
+                b.get_rook_captures_par(&CaptureRules::new());
             }),
             "single_rook".to_string(),
         )
         .add_function(
-            Box::new(|b: &Board| {
-                b.get_rooks_captures_par();
+            Box::new(|(b, _, _): &Boards| {
+                b.get_rooks_captures_par(&CaptureRules::new());
             }),
             "multiple_rooks".to_string(),
         )
-        .bench(|s: usize| BoardGenerator::new(s as u32).generate());
+        .add_function(
+            Box::new(|(b, _, _): &Boards| {
+                b.get_rooks_captures_par_with(&CaptureRules::new(), Deduplication::AtomicBitset);
+            }),
+            "multiple_rooks_atomic_bitset".to_string(),
+        )
+        .add_function(
+            Box::new(|(b, _, _): &Boards| {
+                b.get_rooks_captures_par_with(&CaptureRules::new(), Deduplication::LocalBitsets);
+            }),
+            "multiple_rooks_local_bitsets".to_string(),
+        )
+        .add_function(
+            Box::new(|(_, b, _): &Boards| {
+                b.get_rooks_captures_par(&CaptureRules::new());
+            }),
+            "multiple_rooks_bitboard".to_string(),
+        )
+        .add_function(
+            Box::new(|(b, _, _): &Boards| {
+                b.get_rooks_captures_sweep(&CaptureRules::new());
+            }),
+            "multiple_rooks_sweep".to_string(),
+        )
+        .add_function(
+            Box::new(|(_, b, _): &Boards| {
+                BoardIndex::new(b);
+            }),
+            "multiple_rooks_index_build".to_string(),
+        )
+        .add_function(
+            Box::new(|(_, _, index): &Boards| {
+                index.captures(&CaptureRules::new());
+            }),
+            "multiple_rooks_index".to_string(),
+        )
+        .bench(|s: usize| {
+            let board = generate(&BoardGenerator::new(s as u32));
+            let bits = board.clone().into_storage();
+            let index = BoardIndex::new(&bits);
+            (board, bits, index)
+        });
 }
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use crate::{Direction, PieceKind};
+
+/// Whether a piece can use a ray to move to an empty square, to
+/// capture the piece at its end, or both.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Mode {
+    /// The ray is used both to move and to capture
+    Both,
+    /// The ray only leads to empty squares
+    MoveOnly,
+    /// The ray only leads to the piece it captures
+    CaptureOnly,
+}
+
+/// A line a piece attacks along: it repeatedly moves by the same offset
+/// of rows and columns, until it meets a piece, leaves the board or has
+/// moved the maximum number of times.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Ray {
+    offset: (i32, i32),
+    range: Option<u32>,
+    mode: Mode,
+}
+
+impl Ray {
+    /// Creates a ray moving by an offset of rows and columns, at most
+    /// `range` times, or until the edge of the board if `range` is
+    /// `None`. The ray is used both to move and to capture.
+    ///
+    /// # Panics
+    /// The function panics if the offset is null.
+    pub fn new(offset: (i32, i32), range: Option<u32>) -> Self {
+        assert_ne!(offset, (0, 0));
+
+        Ray {
+            offset,
+            range,
+            mode: Mode::Both,
+        }
+    }
+
+    /// Returns the offset of rows and columns of a single step.
+    pub fn offset(&self) -> (i32, i32) {
+        self.offset
+    }
+
+    /// Returns the maximum number of steps, if any.
+    pub fn range(&self) -> Option<u32> {
+        self.range
+    }
+
+    /// Returns whether the ray is used to move, to capture, or both.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Returns true if the piece can capture along the ray.
+    pub fn captures(&self) -> bool {
+        self.mode != Mode::MoveOnly
+    }
+
+    /// Returns true if the piece can move to empty squares along the ray.
+    pub fn moves(&self) -> bool {
+        self.mode != Mode::CaptureOnly
+    }
+}
+
+/// Returns the images of an offset by the symmetries of the board, i.e.
+/// all its sign changes and the swap of rows and columns, without
+/// duplicates.
+fn symmetric(offset: (i32, i32)) -> Vec<(i32, i32)> {
+    let (a, b) = offset;
+    let mut offsets = Vec::with_capacity(8);
+
+    [(a, b), (b, a)].iter().for_each(|&(rows, cols)| {
+        [(rows, cols), (rows, -cols), (-rows, cols), (-rows, -cols)]
+            .iter()
+            .for_each(|o| {
+                if !offsets.contains(o) {
+                    offsets.push(*o);
+                }
+            })
+    });
+
+    offsets
+}
+
+/// Describes the way a piece attacks, as a set of rays. Sliders move
+/// along directions until they meet a piece, possibly within a maximum
+/// range, and leapers jump to squares at given offsets whatever stands
+/// in between. Compound pieces are built by combining movements.
+///
+/// # Examples
+/// ```
+/// # use chess::{Direction, Movement};
+/// // The chancellor moves as a rook and as a knight
+/// let chancellor = Movement::slider(&Direction::orthogonal(), None).and(Movement::leaper((1, 2)));
+///
+/// assert_eq!(chancellor.rays().len(), 12);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Movement {
+    rays: Vec<Ray>,
+}
+
+impl Movement {
+    /// Creates a movement without any ray: the piece attacks nothing.
+    pub fn new() -> Self {
+        Movement { rays: Vec::new() }
+    }
+
+    /// Creates a movement from its rays.
+    pub fn from_rays(rays: Vec<Ray>) -> Self {
+        Movement { rays }
+    }
+
+    /// Creates a movement sliding along the given directions, at most
+    /// `range` squares away, or until the edge of the board if `range`
+    /// is `None`.
+    pub fn slider(directions: &[Direction], range: Option<u32>) -> Self {
+        Movement {
+            rays: directions
+                .iter()
+                .map(|d| Ray::new(d.get_vector(), range))
+                .collect(),
+        }
+    }
+
+    /// Creates a movement leaping to the squares at an offset of rows
+    /// and columns, in every orientation: `(1, 2)` is the knight, `(1, 3)`
+    /// the camel and `(2, 3)` the zebra.
+    pub fn leaper(offset: (i32, i32)) -> Self {
+        Movement::rider(offset, Some(1))
+    }
+
+    /// Creates a movement repeating a leap in every orientation, at most
+    /// `range` times, or until the edge of the board if `range` is `None`.
+    /// The squares jumped over are ignored, but the ray stops at the
+    /// first piece it lands on.
+    pub fn rider(offset: (i32, i32), range: Option<u32>) -> Self {
+        Movement {
+            rays: symmetric(offset)
+                .into_iter()
+                .map(|o| Ray::new(o, range))
+                .collect(),
+        }
+    }
+
+    /// Combines two movements: the piece attacks along the rays of both.
+    /// Rays already present are not repeated.
+    pub fn and(mut self, other: Movement) -> Self {
+        other.rays.into_iter().for_each(|r| {
+            if !self.rays.contains(&r) {
+                self.rays.push(r);
+            }
+        });
+        self
+    }
+
+    /// Restricts every ray of the movement to the given mode.
+    pub fn with_mode(mut self, mode: Mode) -> Self {
+        self.rays.iter_mut().for_each(|r| r.mode = mode);
+        self
+    }
+
+    /// Returns the rays of the movement.
+    pub fn rays(&self) -> &[Ray] {
+        &self.rays
+    }
+}
+
+/// The movements of the pieces that can be placed on a board: the
+/// standard ones, plus custom pieces registered under a name.
+///
+/// # Examples
+/// ```
+/// # use chess::{Board, CaptureRules, Direction, Movement, PieceSet, Position};
+/// let mut pieces = PieceSet::new();
+/// let archbishop = pieces.register(
+///     "archbishop",
+///     Movement::slider(&Direction::diagonal(), None).and(Movement::leaper((1, 2))),
+/// );
+///
+/// let mut board = Board::new(8);
+/// board.add_piece(archbishop, Position::new(4, 4, 8));
+/// board.add_pawn(Position::new(2, 3, 8));
+/// board.add_pawn(Position::new(7, 7, 8));
+/// board.add_pawn(Position::new(4, 6, 8));
+///
+/// let movement = pieces.movement(archbishop).unwrap();
+/// assert_eq!(pieces.name(archbishop), Some("archbishop"));
+/// assert_eq!(board.get_captures_with(archbishop, movement, &CaptureRules::new()), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PieceSet {
+    movements: HashMap<PieceKind, Movement>,
+    names: Vec<String>,
+}
+
+impl PieceSet {
+    /// Creates a set holding the standard pieces only.
+    pub fn new() -> Self {
+        let movements = [
+            PieceKind::Rook,
+            PieceKind::Pawn,
+            PieceKind::Bishop,
+            PieceKind::Queen,
+            PieceKind::King,
+            PieceKind::Knight,
+        ]
+        .iter()
+        .filter_map(|k| k.movement().map(|m| (*k, m)))
+        .collect();
+
+        PieceSet {
+            movements,
+            names: Vec::new(),
+        }
+    }
+
+    /// Registers a custom piece, and returns the kind to use to place it
+    /// on a board.
+    ///
+    /// # Panics
+    /// The function panics if 256 custom pieces are already registered.
+    pub fn register(&mut self, name: &str, movement: Movement) -> PieceKind {
+        assert!(self.names.len() <= u8::MAX as usize);
+
+        let kind = PieceKind::Custom(self.names.len() as u8);
+        self.names.push(name.to_string());
+        self.movements.insert(kind, movement);
+        kind
+    }
+
+    /// Returns the movement of a kind of piece, if it is part of the set.
+    pub fn movement(&self, kind: PieceKind) -> Option<&Movement> {
+        self.movements.get(&kind)
+    }
+
+    /// Returns the name a custom piece was registered with.
+    pub fn name(&self, kind: PieceKind) -> Option<&str> {
+        match kind {
+            PieceKind::Custom(id) => self.names.get(id as usize).map(|n| n.as_str()),
+            _ => None,
+        }
+    }
+}
+
+impl Default for PieceSet {
+    fn default() -> Self {
+        PieceSet::new()
+    }
+}
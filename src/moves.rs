@@ -0,0 +1,41 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{PieceKind, Position};
+
+/// A move played on a board, with what it captured, so that it can be
+/// undone.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Move {
+    pub piece: PieceKind,
+    pub from: Position,
+    pub to: Position,
+    /// The piece standing on the destination before the move, if any
+    pub captured: Option<PieceKind>,
+}
+
+/// The reasons a move can be refused.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum MoveError {
+    /// There is no piece on the starting square
+    NoPiece,
+    /// The piece is missing from the set giving the movements
+    UnknownPiece,
+    /// The piece can't go to the destination from where it stands
+    Unreachable,
+    /// The destination holds a piece the moving piece can't capture
+    Uncapturable,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::NoPiece => write!(f, "no piece to move"),
+            MoveError::UnknownPiece => write!(f, "the piece has no known movement"),
+            MoveError::Unreachable => write!(f, "the piece can't reach the destination"),
+            MoveError::Uncapturable => write!(f, "the piece can't capture the destination"),
+        }
+    }
+}
+
+impl Error for MoveError {}
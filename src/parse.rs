@@ -0,0 +1,351 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use crate::{Board, BoardStorage, PieceKind, Position};
+
+/// The ways the text of a board can be invalid.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ParseErrorKind {
+    /// The first line is not the size of the board
+    InvalidSize,
+    /// A row whose length, given, differs from the size of the board
+    RaggedRow(usize),
+    /// A character that stands neither for a piece nor for empty squares
+    UnknownGlyph(char),
+    /// A custom piece whose id is missing, unclosed, or too large
+    InvalidCustomKind,
+    /// The text ends before the last row of the board
+    MissingRows,
+    /// A row past the last row of the board
+    TooManyRows,
+    /// The reader failed
+    Io(io::ErrorKind),
+}
+
+/// An error met while parsing a board, along with the line and the
+/// column where it was met, both counted from 1. In a board file, the
+/// size of the board is on line 1.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct ParseError {
+    line: usize,
+    column: usize,
+    kind: ParseErrorKind,
+}
+
+impl ParseError {
+    pub(crate) fn new(line: usize, column: usize, kind: ParseErrorKind) -> Self {
+        ParseError { line, column, kind }
+    }
+
+    /// Returns the line of the error.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the column of the error.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns what is wrong with the file.
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+
+        match self.kind {
+            ParseErrorKind::InvalidSize => write!(f, "invalid board size"),
+            ParseErrorKind::RaggedRow(length) => {
+                write!(f, "row of length {} on a board of another size", length)
+            }
+            ParseErrorKind::UnknownGlyph(c) => write!(f, "unknown piece '{}'", c),
+            ParseErrorKind::InvalidCustomKind => write!(f, "invalid custom piece"),
+            ParseErrorKind::MissingRows => write!(f, "missing rows"),
+            ParseErrorKind::TooManyRows => write!(f, "too many rows"),
+            ParseErrorKind::Io(kind) => write!(f, "I/O error: {:?}", kind),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Returns the kind of piece a character stands for, `None` for an
+/// empty square, or the character itself if it is unknown.
+fn glyph(c: char) -> Result<Option<PieceKind>, char> {
+    match c {
+        '.' | ' ' | '_' | '0' => Ok(None),
+        'p' => Ok(Some(PieceKind::Pawn)),
+        'R' => Ok(Some(PieceKind::Rook)),
+        'B' => Ok(Some(PieceKind::Bishop)),
+        'Q' => Ok(Some(PieceKind::Queen)),
+        'K' => Ok(Some(PieceKind::King)),
+        'N' => Ok(Some(PieceKind::Knight)),
+        c => Err(c),
+    }
+}
+
+/// Returns the character standing for a kind of piece, if the format
+/// can describe it.
+fn piece_glyph(kind: PieceKind) -> Option<char> {
+    match kind {
+        PieceKind::Pawn => Some('p'),
+        PieceKind::Rook => Some('R'),
+        PieceKind::Bishop => Some('B'),
+        PieceKind::Queen => Some('Q'),
+        PieceKind::King => Some('K'),
+        PieceKind::Knight => Some('N'),
+        PieceKind::Custom(_) => None,
+    }
+}
+
+impl Board {
+    /// Reads a board file, and returns the board along with the errors
+    /// met. Unless `lenient` is true, the first error stops the parsing
+    /// and is returned alone. Reading can't go on past an invalid size.
+    fn parse<B: BufRead>(r: B, lenient: bool) -> Result<(Board, Vec<ParseError>), ParseError> {
+        let mut lines = r.lines();
+        let size = match lines.next() {
+            Some(Ok(line)) => line.trim().parse::<u32>().ok(),
+            Some(Err(e)) => return Err(ParseError::new(1, 1, ParseErrorKind::Io(e.kind()))),
+            None => None,
+        }
+        .ok_or_else(|| ParseError::new(1, 1, ParseErrorKind::InvalidSize))?;
+
+        let mut errors = Vec::new();
+        let mut report = |error| match lenient {
+            true => {
+                errors.push(error);
+                Ok(())
+            }
+            false => Err(error),
+        };
+
+        let mut pieces = Vec::new();
+        let mut rows = 0;
+        for (i, line) in lines.enumerate() {
+            // Line 1 holds the size
+            let number = i + 2;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    report(ParseError::new(number, 1, ParseErrorKind::Io(e.kind())))?;
+                    break;
+                }
+            };
+
+            // Blank lines may follow the last row
+            if rows == size {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                report(ParseError::new(number, 1, ParseErrorKind::TooManyRows))?;
+                break;
+            }
+
+            let length = line.chars().count();
+            for (col, c) in line.chars().take(size as usize).enumerate() {
+                match glyph(c) {
+                    Ok(Some(kind)) => pieces.push((kind, Position::new(rows, col as u32, size))),
+                    Ok(None) => {}
+                    Err(c) => report(ParseError::new(
+                        number,
+                        col + 1,
+                        ParseErrorKind::UnknownGlyph(c),
+                    ))?,
+                }
+            }
+            if length != size as usize {
+                let column = length.min(size as usize) + 1;
+                report(ParseError::new(
+                    number,
+                    column,
+                    ParseErrorKind::RaggedRow(length),
+                ))?;
+            }
+
+            rows += 1;
+        }
+
+        if rows < size {
+            let number = rows as usize + 2;
+            report(ParseError::new(number, 1, ParseErrorKind::MissingRows))?;
+        }
+
+        let mut board = Board::with_capacity(size, pieces.len());
+        board.set_pieces(pieces);
+        Ok((board, errors))
+    }
+
+    /// Recreates a board from a text file: its size on the first line,
+    /// then one line per row, with a character per square: `.`, a
+    /// space, `_` or `0` for an empty square, and `p`, `R`, `B`, `Q`,
+    /// `K` or `N` for a piece. Returns the first error met, if any.
+    ///
+    /// Unlike earlier versions, which skipped any other character as an
+    /// empty square, this fails on unknown characters and on rows that
+    /// don't match the size of the board.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, ParseErrorKind};
+    /// let board = Board::from_file("3\nR.p\n _0\nB..\n".as_bytes()).unwrap();
+    /// assert_eq!(board.pieces().count(), 3);
+    ///
+    /// let error = Board::from_file("3\nR.p\n..x\n".as_bytes()).err().unwrap();
+    /// assert_eq!((error.line(), error.column()), (3, 3));
+    /// assert_eq!(error.kind(), ParseErrorKind::UnknownGlyph('x'));
+    /// ```
+    pub fn from_file<B: BufRead>(r: B) -> Result<Board, ParseError> {
+        Board::parse(r, false).map(|(board, _)| board)
+    }
+
+    /// Recreates a board from a text file as `from_file` does, but goes
+    /// on after an error, and returns every error met along with the
+    /// board made of the squares that could be read. Fails only if the
+    /// size of the board can't be read.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, ParseErrorKind};
+    /// let (board, errors) = Board::from_file_lenient("3\nR.p.\n..x\n".as_bytes()).unwrap();
+    ///
+    /// assert_eq!(board.pieces().count(), 2);
+    /// assert_eq!(errors.len(), 3);
+    /// assert_eq!(errors[0].kind(), ParseErrorKind::RaggedRow(4));
+    /// assert_eq!(errors[2].kind(), ParseErrorKind::MissingRows);
+    /// ```
+    pub fn from_file_lenient<B: BufRead>(r: B) -> Result<(Board, Vec<ParseError>), ParseError> {
+        Board::parse(r, true)
+    }
+}
+
+impl<S: BoardStorage> Board<S> {
+    /// Writes the board in the format `Board::from_file` reads: its size
+    /// on the first line, then one line per row. Fails without writing
+    /// anything if the board holds custom pieces, which the format
+    /// can't describe.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, Position};
+    /// let mut board = Board::new(3);
+    /// board.add_rook(Position::new(0, 0, 3));
+    /// board.add_pawn(Position::new(2, 1, 3));
+    ///
+    /// let mut file = Vec::new();
+    /// board.write_to(&mut file).unwrap();
+    /// assert_eq!(file, b"3\nR..\n...\n.p.\n");
+    /// ```
+    pub fn write_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        if self.pieces().any(|(k, _)| piece_glyph(k).is_none()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "custom pieces can't be written",
+            ));
+        }
+
+        write!(w, "{}", self)
+    }
+}
+
+/// Shows the board as `Board::write_to` writes it, custom pieces being
+/// shown as `?`.
+///
+/// # Examples
+/// ```
+/// # use chess::{Board, Position};
+/// let mut board = Board::new(3);
+/// board.add_rook(Position::new(0, 0, 3));
+/// board.add_pawn(Position::new(0, 2, 3));
+///
+/// let text = board.to_string();
+/// assert_eq!(text, "3\nR.p\n...\n...\n");
+/// assert_eq!(text.parse::<Board>().unwrap().to_string(), text);
+/// ```
+impl<S: BoardStorage> fmt::Display for Board<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.size() as usize;
+
+        let mut squares = vec!['.'; width * width];
+        self.pieces().for_each(|(k, p)| {
+            squares[p.row() as usize * width + p.col() as usize] = piece_glyph(k).unwrap_or('?')
+        });
+
+        writeln!(f, "{}", self.size())?;
+        squares
+            .chunks(width.max(1))
+            .try_for_each(|row| writeln!(f, "{}", row.iter().collect::<String>()))
+    }
+}
+
+impl FromStr for Board {
+    type Err = ParseError;
+
+    /// Reads a board as `Board::from_file` does.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Board::from_file(s.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::{Board, BoardStorage, GridStorage, PieceKind, Position};
+
+    fn written<S: BoardStorage>(board: &Board<S>) -> io::Result<String> {
+        let mut file = Vec::new();
+        board.write_to(&mut file)?;
+        Ok(String::from_utf8(file).unwrap())
+    }
+
+    #[test]
+    fn write_empty_boards() {
+        assert_eq!(written(&Board::new(3)).unwrap(), "3\n...\n...\n...\n");
+        assert_eq!(written(&Board::new(0)).unwrap(), "0\n");
+        assert_eq!("0\n".parse::<Board>().unwrap().size(), 0);
+    }
+
+    #[test]
+    fn write_single_square() {
+        let mut board = Board::new(1);
+        board.add_rook(Position::new(0, 0, 1));
+
+        assert_eq!(written(&board).unwrap(), "1\nR\n");
+    }
+
+    #[test]
+    fn write_every_kind_at_the_edges() {
+        let text = "3\npRB\n...\nQKN\n";
+        let board = text.parse::<Board>().unwrap();
+
+        assert_eq!(written(&board).unwrap(), text);
+        assert_eq!(written(&board.into_storage::<GridStorage>()).unwrap(), text);
+    }
+
+    #[test]
+    fn write_empty_glyphs_as_dots() {
+        let board = "3\n R_\n0..\n..p\n".parse::<Board>().unwrap();
+        assert_eq!(written(&board).unwrap(), "3\n.R.\n...\n..p\n");
+    }
+
+    #[test]
+    fn write_custom_pieces() {
+        let mut board = Board::new(2);
+        board.add_rook(Position::new(0, 0, 2));
+        board.add_piece(PieceKind::Custom(4), Position::new(1, 1, 2));
+
+        let mut file = Vec::new();
+        let error = board.write_to(&mut file).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(file.is_empty());
+
+        assert_eq!(board.to_string(), "2\nR.\n.?\n");
+    }
+}
@@ -1,10 +1,50 @@
+use crate::{Direction, Mode, Movement, Ray};
+
 /// The different chess pieces used in the problem.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PieceKind {
     /// The white rook, seeking to capture black pawns
     Rook,
     /// The black pawns, can be captured by the rook
     Pawn,
-    /// The white bishop, that can block the path of the white rook
+    /// The white bishop, capturing black pawns along the diagonals
     Bishop,
+    /// The white queen, capturing along the lines and the diagonals
+    Queen,
+    /// The white king, capturing on the eight squares around it
+    King,
+    /// The white knight, capturing by jumping over the other pieces
+    Knight,
+    /// A fairy piece, registered in a `PieceSet` under this id
+    Custom(u8),
+}
+
+impl PieceKind {
+    /// Returns the way the piece moves and attacks. Pawns go down the
+    /// board, moving straight and capturing diagonally. Custom pieces
+    /// have no movement of their own: it is kept in a `PieceSet`.
+    /// Whether a piece attacks at all is up to
+    /// `CaptureRules::is_attacking`.
+    pub fn movement(&self) -> Option<Movement> {
+        let movement = match self {
+            PieceKind::Pawn => Movement::from_rays(vec![Ray::new((1, 0), Some(1))])
+                .with_mode(Mode::MoveOnly)
+                .and(
+                    Movement::from_rays(vec![
+                        Ray::new((1, -1), Some(1)),
+                        Ray::new((1, 1), Some(1)),
+                    ])
+                    .with_mode(Mode::CaptureOnly),
+                ),
+            PieceKind::Rook => Movement::slider(&Direction::orthogonal(), None),
+            PieceKind::Bishop => Movement::slider(&Direction::diagonal(), None),
+            PieceKind::Queen => Movement::slider(&Direction::all_with_diagonals(), None),
+            PieceKind::King => Movement::slider(&Direction::all_with_diagonals(), Some(1)),
+            PieceKind::Knight => Movement::leaper((1, 2)),
+            PieceKind::Custom(_) => return None,
+        };
+
+        Some(movement)
+    }
 }
@@ -0,0 +1,378 @@
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::{
+    Board, BoardIndex, BoardStorage, CaptureRules, Direction, PieceKind, Position, SparseStorage,
+};
+
+/// The lines along which rooks capture, as the empty squares a blocker
+/// could stand on to stop them: every line must get one blocker.
+struct Cover {
+    squares: Vec<Position>,
+    /// The squares of every line
+    lines: Vec<Vec<usize>>,
+    /// The lines going through every square
+    crossing: Vec<Vec<usize>>,
+}
+
+impl Cover {
+    /// Returns a lower bound of the number of blockers the lines not
+    /// stopped yet need: lines sharing no square need one each.
+    fn lower_bound(&self, stopped: &[u32]) -> usize {
+        let mut used = vec![false; self.squares.len()];
+        let mut bound = 0;
+
+        for (line, squares) in self.lines.iter().enumerate() {
+            if stopped[line] == 0 && squares.iter().all(|s| !used[*s]) {
+                squares.iter().for_each(|s| used[*s] = true);
+                bound += 1;
+            }
+        }
+
+        bound
+    }
+
+    /// Branches on the squares of the shortest line not stopped yet,
+    /// and keeps the smallest set of blockers found in `best`.
+    fn search(&self, stopped: &mut [u32], chosen: &mut Vec<usize>, best: &mut Vec<usize>) {
+        let line = (0..self.lines.len())
+            .filter(|l| stopped[*l] == 0)
+            .min_by_key(|l| self.lines[*l].len());
+
+        let line = match line {
+            Some(line) => line,
+            None => {
+                if chosen.len() < best.len() {
+                    *best = chosen.clone();
+                }
+                return;
+            }
+        };
+
+        if chosen.len() + self.lower_bound(stopped) >= best.len() {
+            return;
+        }
+
+        for &square in &self.lines[line] {
+            chosen.push(square);
+            self.crossing[square].iter().for_each(|l| stopped[*l] += 1);
+            self.search(stopped, chosen, best);
+            self.crossing[square].iter().for_each(|l| stopped[*l] -= 1);
+            chosen.pop();
+        }
+    }
+
+    /// Picks the square stopping the most lines until they are all
+    /// stopped, the first square in row order among the equal ones.
+    fn greedy(&self) -> Vec<usize> {
+        let mut open = self.crossing.iter().map(|l| l.len()).collect::<Vec<_>>();
+        let mut stopped = vec![false; self.lines.len()];
+        let key = |s: usize| Reverse((self.squares[s].row(), self.squares[s].col()));
+
+        // Outdated entries are skipped when popped
+        let mut heap = (0..self.squares.len())
+            .map(|s| (open[s], key(s), s))
+            .collect::<BinaryHeap<_>>();
+        let mut chosen = Vec::new();
+
+        while let Some((count, _, square)) = heap.pop() {
+            if count == 0 {
+                break;
+            }
+            if count != open[square] {
+                continue;
+            }
+
+            chosen.push(square);
+            for &line in &self.crossing[square] {
+                if stopped[line] {
+                    continue;
+                }
+                stopped[line] = true;
+                for &s in &self.lines[line] {
+                    open[s] -= 1;
+                    if s != square && open[s] > 0 {
+                        heap.push((open[s], key(s), s));
+                    }
+                }
+            }
+        }
+
+        chosen
+    }
+
+    /// Returns the squares of a set of blockers, in row order.
+    fn positions(&self, chosen: Vec<usize>) -> Vec<Position> {
+        let mut positions = chosen
+            .into_iter()
+            .map(|s| self.squares[s])
+            .collect::<Vec<_>>();
+        positions.sort_by_key(|p| (p.row(), p.col()));
+        positions
+    }
+}
+
+/// An empty square a rook could be added on, and the number of
+/// captures it would add. The gain is negative when the new rook blocks
+/// more captures than it makes.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Placement {
+    pub position: Position,
+    pub gain: i64,
+}
+
+impl<S: BoardStorage> Board<S> {
+    /// Returns a function giving the number of captures a rook added on
+    /// a square would add, or `None` if the square is occupied. The
+    /// pieces are copied into sorted rows and columns, and the rooks
+    /// attacking every square are counted as runs, so the cost of a
+    /// square only depends on the pieces on its row and its column.
+    fn placement_gain<'a>(
+        &self,
+        rules: &'a CaptureRules,
+    ) -> impl Fn(&Position) -> Option<i64> + Sync + 'a {
+        let mut lines = Board::<SparseStorage>::with_storage(self.size());
+        lines.set_pieces(self.pieces().collect());
+        let attackers = self.get_attack_counts(rules);
+        let attacks = rules.is_attacking(PieceKind::Rook);
+
+        // The rooks seeing a square from a direction
+        let rooks = move |lines: &Board<SparseStorage>, position: &Position, d: Direction| {
+            if !attacks {
+                return 0;
+            }
+            rules
+                .sight(*position, |p| lines.first_piece(p, d))
+                .into_iter()
+                .filter(|(k, _)| *k == PieceKind::Rook)
+                .count() as i64
+        };
+
+        move |position: &Position| {
+            if lines.get_piece(position).is_some() {
+                return None;
+            }
+
+            let seen = Direction::orthogonal()
+                .into_iter()
+                .map(|d| rooks(&lines, position, d))
+                .sum::<i64>();
+            // The new rook can be captured itself
+            let mut gain = (rules.is_capturable(PieceKind::Rook) && seen > 0) as i64;
+
+            // Along every line, the new rook attacks the pieces the rooks
+            // behind it attacked through the square, and stops them if it
+            // blocks
+            Direction::orthogonal().into_iter().for_each(|d| {
+                let lost = match rules.is_blocking(PieceKind::Rook) {
+                    true => rooks(&lines, position, d.opposite()),
+                    false => 0,
+                };
+
+                rules
+                    .walk(*position, |p| lines.first_piece(p, d))
+                    .into_iter()
+                    .for_each(|t| {
+                        let before = attackers.get(&t) as i64;
+                        let after = before + attacks as i64 - lost;
+                        gain += (after > 0) as i64 - (before > 0) as i64;
+                    });
+            });
+
+            Some(gain)
+        }
+    }
+
+    /// Returns the number of captures a rook added on every empty square
+    /// would add, row after row. The squares are evaluated in parallel,
+    /// each against the pieces on its row and its column only.
+    pub fn rook_placements(&self, rules: &CaptureRules) -> Vec<Placement> {
+        let size = self.size();
+        let gain = self.placement_gain(rules);
+
+        (0..size)
+            .into_par_iter()
+            .flat_map_iter(|row| (0..size).map(move |col| Position::new(row, col, size)))
+            .filter_map(|position| gain(&position).map(|gain| Placement { position, gain }))
+            .collect()
+    }
+
+    /// Returns the empty squares where adding a rook would add the most
+    /// captures, row after row, all with the same gain over
+    /// `get_rooks_captures`. Only the best squares found so far are
+    /// kept while the rows are evaluated in parallel.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, CaptureRules, Position};
+    /// let rules = CaptureRules::new();
+    /// let mut board = Board::new(8);
+    /// board.add_pawn(Position::new(2, 5, 8));
+    /// board.add_pawn(Position::new(6, 1, 8));
+    ///
+    /// let best = board.best_rook_placements(&rules);
+    ///
+    /// assert_eq!(best.len(), 2);
+    /// assert_eq!(best[0].position, Position::new(2, 1, 8));
+    /// assert_eq!(best[0].gain, 2);
+    /// ```
+    pub fn best_rook_placements(&self, rules: &CaptureRules) -> Vec<Placement> {
+        let size = self.size();
+        let gain = self.placement_gain(rules);
+
+        // Keeps the placements of the best gain, in the order given
+        let best = |mut a: Vec<Placement>, mut b: Vec<Placement>| match (a.first(), b.first()) {
+            (Some(x), Some(y)) if x.gain > y.gain => a,
+            (Some(x), Some(y)) if x.gain < y.gain => b,
+            (None, _) => b,
+            (_, None) => a,
+            _ => {
+                a.append(&mut b);
+                a
+            }
+        };
+
+        (0..size)
+            .into_par_iter()
+            .flat_map_iter(|row| (0..size).map(move |col| Position::new(row, col, size)))
+            .filter_map(|position| gain(&position).map(|gain| Placement { position, gain }))
+            .fold(Vec::new, |mut placements: Vec<Placement>, p| {
+                match placements.first() {
+                    Some(first) if first.gain > p.gain => {}
+                    Some(first) if first.gain == p.gain => placements.push(p),
+                    _ => placements = vec![p],
+                }
+                placements
+            })
+            .reduce(Vec::new, best)
+    }
+
+    /// Adds `count` rooks one after the other, each on the first of the
+    /// best squares left by the previous ones, and returns where they
+    /// were placed. This greedy placement is not always the best one.
+    /// Fewer rooks are placed if the board fills up.
+    pub fn place_rooks_greedy(&mut self, count: usize, rules: &CaptureRules) -> Vec<Placement> {
+        let mut placed = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            match self.best_rook_placements(rules).first() {
+                Some(best) => {
+                    self.add_rook(best.position);
+                    placed.push(*best);
+                }
+                None => break,
+            }
+        }
+
+        placed
+    }
+
+    /// Returns the lines along which the rooks capture, or nothing if
+    /// one of them can't be stopped by a bishop.
+    fn blocker_cover(&self, rules: &CaptureRules) -> Option<Cover> {
+        let size = self.size();
+        let index = BoardIndex::new(self);
+        let attacks = rules.is_attacking(PieceKind::Rook);
+
+        // The empty squares between every rook and the first piece it
+        // captures along each of its lines
+        let lines = self
+            .get_rooks_positions()
+            .par_iter()
+            .filter(|_| attacks)
+            .flat_map_iter(|r| {
+                let index = &index;
+                Direction::orthogonal().into_iter().filter_map(move |d| {
+                    let target = *rules.walk(*r, |p| index.first_piece(p, d)).first()?;
+                    let squares = r
+                        .ray(d, size)
+                        .take_while(|p| *p != target)
+                        .filter(|p| index.get_piece(p).is_none())
+                        .collect::<Vec<_>>();
+                    Some(squares)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let stoppable = rules.is_blocking(PieceKind::Bishop)
+            && !rules.is_capturable(PieceKind::Bishop)
+            && lines.iter().all(|l| !l.is_empty());
+        if !lines.is_empty() && !stoppable {
+            return None;
+        }
+
+        let mut ids = HashMap::new();
+        let mut cover = Cover {
+            squares: Vec::new(),
+            lines: Vec::with_capacity(lines.len()),
+            crossing: Vec::new(),
+        };
+        for (line, squares) in lines.into_iter().enumerate() {
+            let squares = squares
+                .into_iter()
+                .map(|p| {
+                    let id = *ids.entry(p).or_insert_with(|| {
+                        cover.squares.push(p);
+                        cover.crossing.push(Vec::new());
+                        cover.squares.len() - 1
+                    });
+                    cover.crossing[id].push(line);
+                    id
+                })
+                .collect();
+            cover.lines.push(squares);
+        }
+
+        Some(cover)
+    }
+
+    /// Finds the fewest bishops to add on empty squares for
+    /// `get_rooks_captures` to drop to zero, and returns their squares.
+    /// Every capturing line of a rook needs a bishop between the rook
+    /// and the first piece it captures, and the search branches on the
+    /// squares of these lines, cutting the branches that can't beat
+    /// the best set found so far. This takes exponential time: on large
+    /// boards, prefer `blocker_placement_greedy`. Returns `None` if the
+    /// captures can't all be stopped, such as when a rook is right next
+    /// to a piece it captures.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, CaptureRules, Position};
+    /// let rules = CaptureRules::new();
+    /// let mut board = Board::new(8);
+    /// board.add_rook(Position::new(0, 2, 8));
+    /// board.add_rook(Position::new(2, 0, 8));
+    /// board.add_pawn(Position::new(4, 2, 8));
+    /// board.add_pawn(Position::new(2, 4, 8));
+    ///
+    /// let blockers = board.blocker_placement(&rules).unwrap();
+    /// assert_eq!(blockers, vec![Position::new(2, 2, 8)]);
+    ///
+    /// blockers.into_iter().for_each(|p| board.add_bishop(p));
+    /// assert_eq!(board.get_rooks_captures(&rules), 0);
+    /// ```
+    pub fn blocker_placement(&self, rules: &CaptureRules) -> Option<Vec<Position>> {
+        let cover = self.blocker_cover(rules)?;
+
+        // The greedy set is the one to beat
+        let mut best = cover.greedy();
+        let mut stopped = vec![0; cover.lines.len()];
+        cover.search(&mut stopped, &mut Vec::new(), &mut best);
+
+        Some(cover.positions(best))
+    }
+
+    /// Finds a small set of bishops to add on empty squares for
+    /// `get_rooks_captures` to drop to zero, as `blocker_placement`
+    /// does, but greedily: the square stopping the most capturing lines
+    /// left is taken first. The set found is not always the smallest,
+    /// but this suits the large generated boards.
+    pub fn blocker_placement_greedy(&self, rules: &CaptureRules) -> Option<Vec<Position>> {
+        let cover = self.blocker_cover(rules)?;
+        let chosen = cover.greedy();
+
+        Some(cover.positions(chosen))
+    }
+}
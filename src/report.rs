@@ -0,0 +1,138 @@
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::{Board, BoardStorage, CaptureRules, Direction, Position};
+
+/// A capture available on the board: the attacking rook, the piece it
+/// can capture, and where that piece stands relative to the rook.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Capture {
+    pub attacker: Position,
+    pub target: Position,
+    pub direction: Direction,
+    /// The number of squares between the attacker and the target,
+    /// the target included
+    pub distance: u32,
+}
+
+/// Every capture available to the rooks of a board, grouped by
+/// captured piece.
+#[derive(Debug, Clone, Default)]
+pub struct CaptureReport {
+    targets: HashMap<Position, Vec<Capture>>,
+}
+
+impl CaptureReport {
+    fn add(&mut self, capture: Capture) {
+        self.targets
+            .entry(capture.target)
+            .or_default()
+            .push(capture);
+    }
+
+    fn merge(mut self, other: CaptureReport) -> Self {
+        other
+            .targets
+            .into_iter()
+            .for_each(|(p, c)| self.targets.entry(p).or_default().extend(c));
+        self
+    }
+
+    /// Returns the number of pieces that can be captured, each counted
+    /// once, as `Board::get_rooks_captures` does.
+    pub fn count(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// Returns the pieces that can be captured, in no particular order.
+    pub fn targets(&self) -> impl Iterator<Item = Position> + '_ {
+        self.targets.keys().copied()
+    }
+
+    /// Returns the captures of a piece by every rook that can capture it,
+    /// or an empty slice if it can't be captured.
+    pub fn attackers(&self, target: &Position) -> &[Capture] {
+        self.targets.get(target).map_or(&[], |c| c.as_slice())
+    }
+
+    /// Returns every capture, a piece being listed once per rook that
+    /// can capture it.
+    pub fn captures(&self) -> impl Iterator<Item = &Capture> + '_ {
+        self.targets.values().flatten()
+    }
+}
+
+impl<S: BoardStorage> Board<S> {
+    /// Returns the captures available to a rook standing on a position.
+    fn rook_capture_list(&self, start: &Position, rules: &CaptureRules) -> Vec<Capture> {
+        Direction::orthogonal()
+            .into_iter()
+            .flat_map(|d| {
+                self.rook_line(start, d, rules)
+                    .into_iter()
+                    .map(move |target| Capture {
+                        attacker: *start,
+                        target,
+                        direction: d,
+                        distance: target.row().abs_diff(start.row())
+                            + target.col().abs_diff(start.col()),
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the captures available to the rook, as counted by
+    /// `get_rook_captures`. This function assumes that there is only one
+    /// rook on the board.
+    pub fn get_rook_capture_report(&self, rules: &CaptureRules) -> Vec<Capture> {
+        self.rook_capture_list(&self.get_rook_position(), rules)
+    }
+
+    /// Returns the captures available to all the rooks on the board,
+    /// along with every rook able to capture each piece.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, CaptureRules, Direction, Position};
+    /// let mut board = Board::new(8);
+    /// board.add_rook(Position::new(0, 2, 8));
+    /// board.add_rook(Position::new(4, 6, 8));
+    /// board.add_pawn(Position::new(4, 2, 8));
+    ///
+    /// let rules = CaptureRules::new();
+    /// let report = board.get_rooks_capture_report(&rules);
+    /// let pawn = Position::new(4, 2, 8);
+    ///
+    /// assert_eq!(report.count(), board.get_rooks_captures(&rules));
+    /// assert_eq!(report.attackers(&pawn).len(), 2);
+    /// assert!(report
+    ///     .attackers(&pawn)
+    ///     .iter()
+    ///     .any(|c| c.direction == Direction::West && c.distance == 4));
+    /// ```
+    pub fn get_rooks_capture_report(&self, rules: &CaptureRules) -> CaptureReport {
+        let mut report = CaptureReport::default();
+
+        self.get_rooks_positions()
+            .iter()
+            .flat_map(|start| self.rook_capture_list(start, rules))
+            .for_each(|c| report.add(c));
+        report
+    }
+
+    /// Returns the same report as `get_rooks_capture_report`, in
+    /// parallel on the rooks as `get_rooks_captures_par` does. The
+    /// reports of the rooks are merged two by two.
+    pub fn get_rooks_capture_report_par(&self, rules: &CaptureRules) -> CaptureReport {
+        self.get_rooks_positions()
+            .par_iter()
+            .map(|start| {
+                let mut report = CaptureReport::default();
+                self.rook_capture_list(start, rules)
+                    .into_iter()
+                    .for_each(|c| report.add(c));
+                report
+            })
+            .reduce(CaptureReport::default, CaptureReport::merge)
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{PieceKind, Position};
+
+/// A set of piece kinds, as one bit per kind: the standard kinds first,
+/// then every possible custom kind.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+struct KindSet([u64; 5]);
+
+impl KindSet {
+    fn bit(kind: PieceKind) -> usize {
+        match kind {
+            PieceKind::Rook => 0,
+            PieceKind::Pawn => 1,
+            PieceKind::Bishop => 2,
+            PieceKind::Queen => 3,
+            PieceKind::King => 4,
+            PieceKind::Knight => 5,
+            PieceKind::Custom(id) => 6 + id as usize,
+        }
+    }
+
+    fn set(&mut self, kind: PieceKind, value: bool) {
+        let bit = KindSet::bit(kind);
+        if value {
+            self.0[bit / 64] |= 1 << (bit % 64);
+        } else {
+            self.0[bit / 64] &= !(1 << (bit % 64));
+        }
+    }
+
+    fn contains(&self, kind: PieceKind) -> bool {
+        let bit = KindSet::bit(kind);
+        self.0[bit / 64] & (1 << (bit % 64)) != 0
+    }
+}
+
+/// States which kinds of pieces can be captured, which ones block the
+/// rays of the attacking pieces, and which ones attack.
+///
+/// Walking along a ray, an attacking piece captures every capturable
+/// piece it meets, and stops at the first blocking one. A piece that
+/// doesn't block is seen through, whether it is captured or not.
+///
+/// # Examples
+/// ```
+/// # use chess::{Board, CaptureRules, PieceKind, Position};
+/// let mut board = Board::new(8);
+/// board.add_rook(Position::new(0, 0, 8));
+/// board.add_pawn(Position::new(0, 3, 8));
+/// board.add_pawn(Position::new(0, 5, 8));
+/// board.add_bishop(Position::new(4, 0, 8));
+///
+/// assert_eq!(board.get_rooks_captures(&CaptureRules::new()), 1);
+///
+/// let rules = CaptureRules::new()
+///     .capturable(PieceKind::Bishop, true)
+///     .blocking(PieceKind::Pawn, false);
+/// assert_eq!(board.get_rooks_captures(&rules), 3);
+/// ```
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct CaptureRules {
+    capturable: KindSet,
+    transparent: KindSet,
+    passive: KindSet,
+}
+
+impl CaptureRules {
+    /// Creates the rules of the original problem: only pawns can be
+    /// captured, every piece blocks, and every piece but the pawns
+    /// attacks.
+    pub fn new() -> Self {
+        let mut capturable = KindSet([0; 5]);
+        capturable.set(PieceKind::Pawn, true);
+
+        CaptureRules {
+            capturable,
+            transparent: KindSet([0; 5]),
+            passive: capturable,
+        }
+    }
+
+    /// Sets whether pieces of a kind can be captured.
+    pub fn capturable(mut self, kind: PieceKind, capturable: bool) -> Self {
+        self.capturable.set(kind, capturable);
+        self
+    }
+
+    /// Sets whether pieces of a kind stop the rays going through them.
+    pub fn blocking(mut self, kind: PieceKind, blocking: bool) -> Self {
+        self.transparent.set(kind, !blocking);
+        self
+    }
+
+    /// Sets whether pieces of a kind capture other pieces.
+    pub fn attacking(mut self, kind: PieceKind, attacking: bool) -> Self {
+        self.passive.set(kind, !attacking);
+        self
+    }
+
+    /// Returns true if pieces of a kind can be captured.
+    pub fn is_capturable(&self, kind: PieceKind) -> bool {
+        self.capturable.contains(kind)
+    }
+
+    /// Returns true if pieces of a kind stop the rays going through them.
+    pub fn is_blocking(&self, kind: PieceKind) -> bool {
+        !self.transparent.contains(kind)
+    }
+
+    /// Returns true if pieces of a kind capture other pieces.
+    pub fn is_attacking(&self, kind: PieceKind) -> bool {
+        !self.passive.contains(kind)
+    }
+
+    /// Walks a ray from a position, and returns the pieces seen along
+    /// it, up to and including the first blocking one. `next` returns
+    /// the first piece met after a position on the ray, if any.
+    pub(crate) fn sight<F>(&self, start: Position, mut next: F) -> Vec<(PieceKind, Position)>
+    where
+        F: FnMut(&Position) -> Option<(PieceKind, Position)>,
+    {
+        let mut seen = Vec::new();
+        let mut current = start;
+
+        while let Some((k, p)) = next(&current) {
+            seen.push((k, p));
+            if self.is_blocking(k) {
+                break;
+            }
+            current = p;
+        }
+
+        seen
+    }
+
+    /// Walks a ray from a position as `sight` does, and returns the
+    /// pieces captured along it.
+    pub(crate) fn walk<F>(&self, start: Position, next: F) -> Vec<Position>
+    where
+        F: FnMut(&Position) -> Option<(PieceKind, Position)>,
+    {
+        self.sight(start, next)
+            .into_iter()
+            .filter_map(|(k, p)| if self.is_capturable(k) { Some(p) } else { None })
+            .collect()
+    }
+}
+
+impl Default for CaptureRules {
+    fn default() -> Self {
+        CaptureRules::new()
+    }
+}
@@ -0,0 +1,213 @@
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashSet;
+
+use crate::{BenchmarkResult, Board, BoardStorage, PieceKind, Position};
+
+/// The version of the JSON schema written for boards and benchmark
+/// results. Documents without a version were written before versions
+/// existed, and are read as version 1.
+///
+/// - Version 1: the first schema.
+pub const SCHEMA_VERSION: u32 = 1;
+
+fn first_version() -> u32 {
+    1
+}
+
+/// Fails on documents written by a newer version of the schema.
+fn check_version<E: Error>(version: u32) -> Result<(), E> {
+    if version > SCHEMA_VERSION {
+        Err(E::custom(format!(
+            "unsupported schema version {}, the latest is {}",
+            version, SCHEMA_VERSION
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// A piece of a board document.
+#[derive(Serialize, Deserialize)]
+struct PieceDocument {
+    kind: PieceKind,
+    row: u32,
+    col: u32,
+}
+
+/// A board, as written in JSON.
+#[derive(Serialize, Deserialize)]
+struct BoardDocument {
+    #[serde(default = "first_version")]
+    version: u32,
+    size: u32,
+    pieces: Vec<PieceDocument>,
+}
+
+/// Writes the board as a JSON object holding the version of the schema,
+/// the size of the board, and its pieces, row after row:
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "size": 8,
+///   "pieces": [
+///     { "kind": "Rook", "row": 0, "col": 0 },
+///     { "kind": { "Custom": 3 }, "row": 0, "col": 5 }
+///   ]
+/// }
+/// ```
+///
+/// A kind is the name of a `PieceKind`, or an object holding the id of
+/// a custom piece. The moves played are not written.
+///
+/// # Examples
+/// ```
+/// # use chess::{Board, BitStorage, Position};
+/// let mut board = Board::new(8);
+/// board.add_rook(Position::new(0, 0, 8));
+/// board.add_pawn(Position::new(0, 5, 8));
+///
+/// let json = serde_json::to_string(&board).unwrap();
+/// let read: Board<BitStorage> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(read.to_string(), board.to_string());
+///
+/// let json = r#"{ "version": 1, "size": 2, "pieces": [{ "kind": "Pawn", "row": 2, "col": 0 }] }"#;
+/// assert!(serde_json::from_str::<Board>(json).is_err());
+/// ```
+impl<S: BoardStorage> Serialize for Board<S> {
+    fn serialize<T: Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        let mut pieces = self
+            .pieces()
+            .map(|(kind, p)| PieceDocument {
+                kind,
+                row: p.row(),
+                col: p.col(),
+            })
+            .collect::<Vec<_>>();
+        pieces.sort_by_key(|p| (p.row, p.col));
+
+        BoardDocument {
+            version: SCHEMA_VERSION,
+            size: self.size(),
+            pieces,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Reads a board written as `Serialize` describes, and fails on pieces
+/// off the board or on the same square.
+impl<'de, S: BoardStorage> Deserialize<'de> for Board<S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let document = BoardDocument::deserialize(deserializer)?;
+        check_version(document.version)?;
+
+        let size = document.size;
+        let mut squares = HashSet::with_capacity(document.pieces.len());
+        let pieces = document
+            .pieces
+            .into_iter()
+            .map(|p| {
+                if p.row >= size || p.col >= size {
+                    return Err(D::Error::custom(format!(
+                        "piece at ({}, {}) off a board of size {}",
+                        p.row, p.col, size
+                    )));
+                }
+                if !squares.insert((p.row, p.col)) {
+                    return Err(D::Error::custom(format!(
+                        "two pieces at ({}, {})",
+                        p.row, p.col
+                    )));
+                }
+                Ok((p.kind, Position::new(p.row, p.col, size)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut board = Board::with_storage(size);
+        board.set_pieces(pieces);
+        Ok(board)
+    }
+}
+
+/// A benchmark result, as written in JSON.
+#[derive(Serialize, Deserialize)]
+struct BenchmarkDocument {
+    #[serde(default = "first_version")]
+    version: u32,
+    functions: Vec<String>,
+    threads: Vec<usize>,
+    sizes: Vec<usize>,
+    data: Vec<Vec<Vec<Vec<u64>>>>,
+}
+
+/// Writes the result as a JSON object holding the version of the
+/// schema, the names of the functions, the numbers of threads, the
+/// sizes of the boards, and the times measured, as `data` returns them:
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "functions": ["multiple_rooks"],
+///   "threads": [1, 2],
+///   "sizes": [256],
+///   "data": [[[[1200], [1100]]], [[[700], [650]]]]
+/// }
+/// ```
+///
+/// # Examples
+/// ```
+/// # use chess::BenchmarkResult;
+/// let json = r#"{
+///     "functions": ["multiple_rooks"],
+///     "threads": [1, 2],
+///     "sizes": [256],
+///     "data": [[[[1200], [1100]]], [[[700], [650]]]]
+/// }"#;
+///
+/// let result: BenchmarkResult = serde_json::from_str(json).unwrap();
+/// assert_eq!(result.data()[1][0][1][0], 650);
+/// ```
+impl Serialize for BenchmarkResult {
+    fn serialize<T: Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        BenchmarkDocument {
+            version: SCHEMA_VERSION,
+            functions: self.functions().to_vec(),
+            threads: self.threads().to_vec(),
+            sizes: self.sizes().to_vec(),
+            data: self.data().to_vec(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Reads a benchmark result written as `Serialize` describes, and fails
+/// if the times don't match the threads, the sizes and the functions.
+impl<'de> Deserialize<'de> for BenchmarkResult {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let document = BenchmarkDocument::deserialize(deserializer)?;
+        check_version(document.version)?;
+
+        let consistent = document.data.len() == document.threads.len()
+            && document.data.iter().all(|sizes| {
+                sizes.len() == document.sizes.len()
+                    && sizes
+                        .iter()
+                        .flatten()
+                        .all(|run| run.len() == document.functions.len())
+            });
+        if !consistent {
+            return Err(D::Error::custom(
+                "times don't match the threads, sizes and functions",
+            ));
+        }
+
+        Ok(BenchmarkResult::new(
+            document.data,
+            document.functions,
+            document.threads,
+            document.sizes,
+        ))
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::storage::walk_line;
+use crate::{BoardStorage, Direction, PieceKind, Position};
+
+/// The occupied squares of a family of parallel lines, by line and by
+/// coordinate along the line.
+type Lines = HashMap<u32, BTreeMap<u32, PieceKind>>;
+
+fn add(lines: &mut Lines, line: u32, coord: u32, piece: PieceKind) {
+    lines.entry(line).or_default().insert(coord, piece);
+}
+
+fn take(lines: &mut Lines, line: u32, coord: u32) {
+    if let Some(l) = lines.get_mut(&line) {
+        l.remove(&coord);
+        if l.is_empty() {
+            lines.remove(&line);
+        }
+    }
+}
+
+fn before(lines: &Lines, line: u32, coord: u32) -> Option<(u32, PieceKind)> {
+    lines
+        .get(&line)
+        .and_then(|l| l.range(..coord).next_back())
+        .map(|(&c, &k)| (c, k))
+}
+
+fn after(lines: &Lines, line: u32, coord: u32) -> Option<(u32, PieceKind)> {
+    lines
+        .get(&line)
+        .and_then(|l| l.range(coord + 1..).next())
+        .map(|(&c, &k)| (c, k))
+}
+
+/// Stores only the occupied squares, as sorted maps kept for every
+/// non-empty row and column. The memory used only depends on the
+/// number of pieces, and the first piece along a row or a column is
+/// found in logarithmic time, so capture counting scales with the
+/// number of pieces and not with the size of the board.
+///
+/// # Examples
+/// ```
+/// # use chess::{Board, CaptureRules, Position, SparseStorage};
+/// let size = 1_000_000;
+/// let mut board = Board::<SparseStorage>::with_storage(size);
+/// board.add_rook(Position::new(500_000, 3, size));
+/// board.add_pawn(Position::new(500_000, 999_999, size));
+/// board.add_pawn(Position::new(3, 3, size));
+///
+/// assert_eq!(board.get_rooks_captures(&CaptureRules::new()), 2);
+/// ```
+#[derive(Clone)]
+pub struct SparseStorage {
+    size: u32,
+    /// Keyed by row, ordered by column.
+    rows: Lines,
+    /// Keyed by column, ordered by row.
+    columns: Lines,
+}
+
+impl BoardStorage for SparseStorage {
+    fn new(size: u32) -> Self {
+        SparseStorage {
+            size,
+            rows: HashMap::new(),
+            columns: HashMap::new(),
+        }
+    }
+
+    fn get(&self, position: &Position) -> Option<PieceKind> {
+        self.rows
+            .get(&position.row())
+            .and_then(|row| row.get(&position.col()))
+            .copied()
+    }
+
+    fn insert(&mut self, piece: PieceKind, position: Position) {
+        let (row, col) = (position.row(), position.col());
+
+        add(&mut self.rows, row, col, piece);
+        add(&mut self.columns, col, row, piece);
+    }
+
+    fn remove(&mut self, position: &Position) -> Option<PieceKind> {
+        let kind = self.get(position)?;
+        let (row, col) = (position.row(), position.col());
+
+        take(&mut self.rows, row, col);
+        take(&mut self.columns, col, row);
+
+        Some(kind)
+    }
+
+    fn clear(&mut self) {
+        self.rows.clear();
+        self.columns.clear();
+    }
+
+    fn pieces(&self) -> Box<dyn Iterator<Item = (PieceKind, Position)> + '_> {
+        let size = self.size;
+
+        Box::new(self.rows.iter().flat_map(move |(&row, cols)| {
+            cols.iter()
+                .map(move |(&col, &k)| (k, Position::new(row, col, size)))
+        }))
+    }
+
+    fn first_piece(
+        &self,
+        position: &Position,
+        direction: Direction,
+        size: u32,
+    ) -> Option<(PieceKind, Position)> {
+        let (row, col) = (position.row(), position.col());
+
+        let found = match direction {
+            Direction::East => after(&self.rows, row, col).map(|(c, k)| (k, row, c)),
+            Direction::West => before(&self.rows, row, col).map(|(c, k)| (k, row, c)),
+            Direction::South => after(&self.columns, col, row).map(|(r, k)| (k, r, col)),
+            Direction::North => before(&self.columns, col, row).map(|(r, k)| (k, r, col)),
+            // Diagonals are not indexed, so they are walked square by square
+            _ => return walk_line(self, position, direction, size),
+        };
+
+        found.map(|(k, row, col)| (k, Position::new(row, col, size)))
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{Direction, PieceKind, Position};
+
+/// The way the pieces of a `Board` are stored in memory. Every
+/// backend must be able to answer the same questions, so that all
+/// the capture functions of the board work with any of them; they
+/// only differ in the cost of each operation.
+pub trait BoardStorage: Send + Sync {
+    /// Creates an empty storage for a board of a given size.
+    fn new(size: u32) -> Self;
+
+    /// Creates an empty storage for a board of a given size, with
+    /// room for at least `pieces` pieces.
+    fn with_capacity(size: u32, pieces: usize) -> Self
+    where
+        Self: Sized,
+    {
+        let _ = pieces;
+        Self::new(size)
+    }
+
+    /// Returns the kind of piece present on a certain position, if any.
+    fn get(&self, position: &Position) -> Option<PieceKind>;
+
+    /// Puts a piece on a square. The square is assumed to be empty.
+    fn insert(&mut self, piece: PieceKind, position: Position);
+
+    /// Removes the piece on a square, and returns its kind if there
+    /// was one.
+    fn remove(&mut self, position: &Position) -> Option<PieceKind>;
+
+    /// Removes every piece.
+    fn clear(&mut self);
+
+    /// Returns all the pieces stored, in no particular order.
+    fn pieces(&self) -> Box<dyn Iterator<Item = (PieceKind, Position)> + '_>;
+
+    /// Returns the first piece met when walking from `position`
+    /// (excluded) in `direction`, along with its position. By default,
+    /// every square of the line is looked up in turn.
+    fn first_piece(
+        &self,
+        position: &Position,
+        direction: Direction,
+        size: u32,
+    ) -> Option<(PieceKind, Position)> {
+        walk_line(self, position, direction, size)
+    }
+}
+
+/// Finds the first piece in a direction by looking up every square of
+/// the line in turn. This is what backends fall back to for the
+/// directions they have no faster way to scan.
+pub(crate) fn walk_line<S: BoardStorage + ?Sized>(
+    storage: &S,
+    position: &Position,
+    direction: Direction,
+    size: u32,
+) -> Option<(PieceKind, Position)> {
+    position
+        .ray(direction, size)
+        .find_map(|p| storage.get(&p).map(|k| (k, p)))
+}
+
+/// Stores the pieces as a plain list. Looking up a square is a linear
+/// scan over all the pieces, but the memory used only depends on the
+/// number of pieces.
+#[derive(Clone)]
+pub struct ListStorage {
+    pieces: Vec<(PieceKind, Position)>,
+}
+
+impl BoardStorage for ListStorage {
+    fn new(_size: u32) -> Self {
+        ListStorage { pieces: Vec::new() }
+    }
+
+    fn with_capacity(_size: u32, pieces: usize) -> Self {
+        ListStorage {
+            pieces: Vec::with_capacity(pieces),
+        }
+    }
+
+    fn get(&self, position: &Position) -> Option<PieceKind> {
+        self.pieces
+            .iter()
+            .find(|(_, p)| p == position)
+            .map(|(k, _)| *k)
+    }
+
+    fn insert(&mut self, piece: PieceKind, position: Position) {
+        self.pieces.push((piece, position));
+    }
+
+    fn remove(&mut self, position: &Position) -> Option<PieceKind> {
+        let index = self.pieces.iter().position(|(_, p)| p == position)?;
+        Some(self.pieces.swap_remove(index).0)
+    }
+
+    fn clear(&mut self) {
+        self.pieces.clear();
+    }
+
+    fn pieces(&self) -> Box<dyn Iterator<Item = (PieceKind, Position)> + '_> {
+        Box::new(self.pieces.iter().copied())
+    }
+}
+
+/// Stores the pieces in a dense grid, indexed by row and column.
+/// Looking up a square is constant time, but the memory used grows
+/// with the square of the board size.
+#[derive(Clone)]
+pub struct GridStorage {
+    size: u32,
+    squares: Vec<Option<PieceKind>>,
+}
+
+impl GridStorage {
+    fn index(&self, position: &Position) -> usize {
+        position.row() as usize * self.size as usize + position.col() as usize
+    }
+}
+
+impl BoardStorage for GridStorage {
+    fn new(size: u32) -> Self {
+        GridStorage {
+            size,
+            squares: vec![None; size as usize * size as usize],
+        }
+    }
+
+    fn get(&self, position: &Position) -> Option<PieceKind> {
+        self.squares[self.index(position)]
+    }
+
+    fn insert(&mut self, piece: PieceKind, position: Position) {
+        let index = self.index(&position);
+        self.squares[index] = Some(piece);
+    }
+
+    fn remove(&mut self, position: &Position) -> Option<PieceKind> {
+        let index = self.index(position);
+        self.squares[index].take()
+    }
+
+    fn clear(&mut self) {
+        self.squares.iter_mut().for_each(|s| *s = None);
+    }
+
+    fn pieces(&self) -> Box<dyn Iterator<Item = (PieceKind, Position)> + '_> {
+        let size = self.size;
+        let width = size as usize;
+
+        Box::new(self.squares.iter().enumerate().filter_map(move |(i, s)| {
+            s.map(|k| {
+                let (row, col) = ((i / width) as u32, (i % width) as u32);
+                (k, Position::new(row, col, size))
+            })
+        }))
+    }
+}
@@ -0,0 +1,202 @@
+use std::io::{self, BufRead};
+
+use crate::board::line_captures;
+use crate::{CaptureRules, PieceKind};
+
+/// What can be seen looking up a column from below the rows read so
+/// far, through the pieces that don't block.
+#[derive(Copy, Clone, Default)]
+struct Column {
+    /// Whether a rook can be seen
+    rook: bool,
+    /// The number of capturable pieces that can be seen, and that
+    /// haven't been captured yet
+    pending: usize,
+}
+
+/// Counts the pieces the rooks of a board can capture, reading the
+/// board one row at a time in the text format used by
+/// `Board::from_file`. Only what can be seen looking up every column is
+/// remembered, so the memory used only depends on the width of the
+/// board, and the board itself is never built.
+///
+/// A piece is captured when a rook sees it along its row or its
+/// column, and is counted once, as with `Board::get_rooks_captures`.
+///
+/// # Examples
+/// ```
+/// # use chess::{CaptureRules, CaptureStream};
+/// let text = "4\n.R.p\n....\nBp.R\n.R..\n";
+/// let captures = CaptureStream::count(text.as_bytes(), &CaptureRules::new()).unwrap();
+///
+/// assert_eq!(captures, 2);
+/// ```
+pub struct CaptureStream {
+    size: u32,
+    rows: u32,
+    rules: CaptureRules,
+    columns: Vec<Column>,
+    captures: usize,
+}
+
+impl CaptureStream {
+    /// Creates a counter for a board of a given size, before any row
+    /// has been read.
+    pub fn new(size: u32, rules: &CaptureRules) -> Self {
+        CaptureStream {
+            size,
+            rows: 0,
+            rules: *rules,
+            columns: vec![Column::default(); size as usize],
+            captures: 0,
+        }
+    }
+
+    /// Reads a whole board from a reader and returns the number of
+    /// pieces that can be captured.
+    pub fn count<B: BufRead>(mut r: B, rules: &CaptureRules) -> io::Result<usize> {
+        let mut line = String::new();
+        r.read_line(&mut line)?;
+
+        let size = line
+            .trim()
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let mut stream = CaptureStream::new(size, rules);
+
+        loop {
+            line.clear();
+            if r.read_line(&mut line)? == 0 {
+                break;
+            }
+            stream.push_row(line.trim_end_matches(&['\n', '\r'][..]));
+        }
+
+        Ok(stream.captures())
+    }
+
+    /// Reads the next row of the board. Rows past the size of the
+    /// board, and squares past its width, are ignored.
+    pub fn push_row(&mut self, row: &str) {
+        if self.rows == self.size {
+            return;
+        }
+        self.rows += 1;
+
+        let rules = self.rules;
+        let attacks = |k| k == PieceKind::Rook && rules.is_attacking(PieceKind::Rook);
+
+        let pieces = row
+            .chars()
+            .take(self.size as usize)
+            .enumerate()
+            .filter_map(|(col, c)| match c {
+                'p' => Some((col, PieceKind::Pawn)),
+                'R' => Some((col, PieceKind::Rook)),
+                'B' => Some((col, PieceKind::Bishop)),
+                'Q' => Some((col, PieceKind::Queen)),
+                'K' => Some((col, PieceKind::King)),
+                'N' => Some((col, PieceKind::Knight)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // Pieces captured along the row, in the same order as the pieces
+        let mut captured = line_captures(pieces.iter().copied(), &rules)
+            .into_iter()
+            .peekable();
+        let captured = pieces
+            .iter()
+            .map(|(col, _)| captured.next_if_eq(col).is_some())
+            .collect::<Vec<_>>();
+
+        pieces
+            .iter()
+            .zip(captured)
+            .for_each(|(&(col, kind), captured)| {
+                let column = &mut self.columns[col];
+
+                // Captured along the row, or by a rook above it
+                let capturable = rules.is_capturable(kind);
+                let captured = capturable && (captured || column.rook);
+                if captured {
+                    self.captures += 1;
+                }
+
+                // A rook captures the pieces waiting above it
+                if attacks(kind) {
+                    self.captures += column.pending;
+                    column.pending = 0;
+                }
+                if rules.is_blocking(kind) {
+                    *column = Column::default();
+                }
+                if attacks(kind) {
+                    column.rook = true;
+                }
+                if capturable && !captured {
+                    column.pending += 1;
+                }
+            });
+    }
+
+    /// Returns the number of pieces that can be captured on the rows
+    /// read so far. Pieces may still be captured by rooks on rows that
+    /// have not been read yet.
+    pub fn captures(&self) -> usize {
+        self.captures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CaptureRules, CaptureStream, PieceKind};
+
+    fn count(text: &str, rules: &CaptureRules) -> usize {
+        CaptureStream::count(text.as_bytes(), rules).unwrap()
+    }
+
+    #[test]
+    fn shared_pieces_counted_once() {
+        let text = "5\nR.p.R\n.....\np.B.p\n.....\nR.p.p\n";
+        assert_eq!(count(text, &CaptureRules::new()), 4);
+
+        let rules = CaptureRules::new().blocking(PieceKind::Pawn, false);
+        assert_eq!(count(text, &rules), 5);
+    }
+
+    #[test]
+    fn captures_row_by_row() {
+        let mut stream = CaptureStream::new(5, &CaptureRules::new());
+        let captures = ["R.p.R", ".....", "p.B.p", ".....", "R.p.p"]
+            .iter()
+            .map(|row| {
+                stream.push_row(row);
+                stream.captures()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(captures, vec![1, 1, 3, 3, 4]);
+    }
+
+    #[test]
+    fn pieces_captured_from_below() {
+        assert_eq!(count("3\n..p\n...\n..R\n", &CaptureRules::new()), 1);
+        assert_eq!(count("3\nR.p\n...\n..R\n", &CaptureRules::new()), 1);
+    }
+
+    #[test]
+    fn adjacent_pieces() {
+        assert_eq!(count("2\nRp\npR\n", &CaptureRules::new()), 2);
+    }
+
+    #[test]
+    fn empty_boards_and_extra_rows() {
+        let rules = CaptureRules::new();
+        assert_eq!(count("3\n...\n...\n...\n", &rules), 0);
+        assert_eq!(count("1\nR\n", &rules), 0);
+
+        // The rook is past the last row, and is ignored
+        assert_eq!(count("1\np\nR\n", &rules), 0);
+    }
+}
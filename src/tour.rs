@@ -0,0 +1,161 @@
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::{Board, BoardStorage, CaptureRules, Direction, Move, PieceKind, PieceSet, Position};
+
+/// The pieces of a board, sorted by square, and a number of moves left.
+type State = (Vec<(Position, PieceKind)>, u32);
+
+/// The best tours found so far, shared between the threads.
+type Memo = Mutex<HashMap<State, CaptureTour>>;
+
+/// A sequence of rook moves, and the number of pieces it captures.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct CaptureTour {
+    pub captures: usize,
+    pub moves: Vec<Move>,
+}
+
+impl<S: BoardStorage + Clone> Board<S> {
+    /// Returns the pieces of the board, sorted by square.
+    fn state(&self) -> Vec<(Position, PieceKind)> {
+        let mut pieces = self.pieces().map(|(k, p)| (p, k)).collect::<Vec<_>>();
+        pieces.sort_by_key(|(p, _)| (p.row(), p.col()));
+        pieces
+    }
+
+    /// Returns the moves the rooks can play, captures first. Moves to
+    /// empty squares are left out when `captures_only` is true.
+    fn rook_moves(&self, rules: &CaptureRules, captures_only: bool) -> Vec<(Position, Position)> {
+        let rooks = self.get_rooks_positions();
+        let attacks = rules.is_attacking(PieceKind::Rook);
+
+        let captures = rooks.iter().flat_map(|r| {
+            Direction::orthogonal()
+                .into_iter()
+                .filter_map(move |d| match self.first_piece(r, d) {
+                    Some((k, p)) if attacks && rules.is_capturable(k) => Some((*r, p)),
+                    _ => None,
+                })
+        });
+        let quiet = rooks.iter().flat_map(|r| {
+            Direction::orthogonal().into_iter().flat_map(move |d| {
+                r.ray(d, self.size())
+                    .take_while(move |p| self.get_piece(p).is_none())
+                    .map(move |p| (*r, p))
+            })
+        });
+
+        if captures_only {
+            captures.collect()
+        } else {
+            captures.chain(quiet).collect()
+        }
+    }
+
+    /// Searches the best tour of at most `budget` moves from the current
+    /// position, playing and undoing the moves on the board.
+    fn tour(
+        &mut self,
+        budget: u32,
+        pieces: &PieceSet,
+        rules: &CaptureRules,
+        memo: &Memo,
+    ) -> CaptureTour {
+        if budget == 0 {
+            return CaptureTour::default();
+        }
+
+        let state = (self.state(), budget);
+        if let Some(tour) = memo.lock().unwrap().get(&state) {
+            return tour.clone();
+        }
+
+        // On the last move, only a capture can improve the tour
+        let mut best = CaptureTour::default();
+        for (from, to) in self.rook_moves(rules, budget == 1) {
+            // No tour captures more than one piece per move
+            if best.captures as u32 == budget {
+                break;
+            }
+
+            let played = self.move_piece(&from, to, pieces, rules).unwrap();
+            let rest = self.tour(budget - 1, pieces, rules, memo);
+            self.undo();
+
+            let captures = rest.captures + played.captured.is_some() as usize;
+            if captures > best.captures {
+                let mut moves = vec![played];
+                moves.extend(rest.moves);
+                best = CaptureTour { captures, moves };
+            }
+        }
+
+        memo.lock().unwrap().insert(state, best.clone());
+        best
+    }
+
+    /// Finds the sequence of at most `budget` rook moves capturing the
+    /// most pieces, a capture opening the lines the captured piece
+    /// blocked. The first moves are searched in parallel, each on its
+    /// own copy of the board, and the best tour from every position
+    /// reached is remembered and shared between the threads.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::{Board, CaptureRules, Position};
+    /// let rules = CaptureRules::new();
+    /// let mut board = Board::new(8);
+    /// board.add_rook(Position::new(0, 0, 8));
+    /// board.add_pawn(Position::new(0, 3, 8));
+    /// board.add_pawn(Position::new(5, 3, 8));
+    /// board.add_pawn(Position::new(5, 6, 8));
+    ///
+    /// let tour = board.capture_tour(3, &rules);
+    ///
+    /// assert_eq!(tour.captures, 3);
+    /// assert_eq!(tour.moves[1].to, Position::new(5, 3, 8));
+    /// ```
+    pub fn capture_tour(&self, budget: u32, rules: &CaptureRules) -> CaptureTour {
+        if budget == 0 {
+            return CaptureTour::default();
+        }
+
+        let memo = Mutex::new(HashMap::new());
+        let pieces = PieceSet::new();
+
+        let (_, best) = self
+            .rook_moves(rules, budget == 1)
+            .into_par_iter()
+            .enumerate()
+            .map(|(i, (from, to))| {
+                let mut board = self.clone();
+                let played = board.move_piece(&from, to, &pieces, rules).unwrap();
+                let rest = board.tour(budget - 1, &pieces, rules, &memo);
+
+                let mut moves = vec![played];
+                moves.extend(rest.moves);
+                let captures = rest.captures + played.captured.is_some() as usize;
+
+                (i, CaptureTour { captures, moves })
+            })
+            // The best tour, the first one found among the equal ones
+            .reduce(
+                || (usize::MAX, CaptureTour::default()),
+                |a, b| match a.1.captures.cmp(&b.1.captures) {
+                    std::cmp::Ordering::Less => b,
+                    std::cmp::Ordering::Greater => a,
+                    std::cmp::Ordering::Equal if a.0 <= b.0 => a,
+                    std::cmp::Ordering::Equal => b,
+                },
+            );
+
+        // Moves that capture nothing are not worth playing
+        if best.captures == 0 {
+            CaptureTour::default()
+        } else {
+            best
+        }
+    }
+}
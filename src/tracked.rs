@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use crate::{Board, BoardStorage, CaptureRules, Direction, ListStorage, PieceKind, Position};
+
+/// A board keeping the pieces its rooks can capture up to date while it
+/// is modified. Every change only walks the lines of the rooks that
+/// see the changed square, and those of a rook standing on it, instead
+/// of recomputing the captures of the whole board.
+///
+/// # Examples
+/// ```
+/// # use chess::{Board, CaptureRules, PieceKind, Position, TrackedBoard};
+/// let rules = CaptureRules::new();
+/// let mut board = TrackedBoard::new(Board::new(8), &rules);
+///
+/// board.add_piece(PieceKind::Rook, Position::new(2, 2, 8));
+/// board.add_piece(PieceKind::Pawn, Position::new(2, 6, 8));
+/// board.add_piece(PieceKind::Pawn, Position::new(6, 2, 8));
+/// assert_eq!(board.captures(), 2);
+///
+/// board.add_piece(PieceKind::Bishop, Position::new(2, 4, 8));
+/// assert_eq!(board.captures(), 1);
+///
+/// board.move_piece(&Position::new(2, 2, 8), Position::new(2, 5, 8));
+/// assert_eq!(board.captures(), board.board().get_rooks_captures(&rules));
+/// ```
+pub struct TrackedBoard<S: BoardStorage = ListStorage> {
+    board: Board<S>,
+    rules: CaptureRules,
+    /// The number of rooks able to capture each capturable piece
+    attackers: HashMap<Position, u32>,
+}
+
+impl<S: BoardStorage> TrackedBoard<S> {
+    /// Starts tracking the captures of a board, following the rules.
+    pub fn new(board: Board<S>, rules: &CaptureRules) -> Self {
+        let mut tracked = TrackedBoard {
+            board,
+            rules: *rules,
+            attackers: HashMap::new(),
+        };
+        tracked.recompute();
+        tracked
+    }
+
+    /// Returns the tracked board.
+    pub fn board(&self) -> &Board<S> {
+        &self.board
+    }
+
+    /// Stops tracking the captures, and returns the board.
+    pub fn into_board(self) -> Board<S> {
+        self.board
+    }
+
+    /// Returns the number of pieces the rooks can capture, each counted
+    /// once, as `Board::get_rooks_captures` does.
+    pub fn captures(&self) -> usize {
+        self.attackers.len()
+    }
+
+    /// Returns the pieces the rooks can capture, in no particular order.
+    pub fn captured(&self) -> impl Iterator<Item = Position> + '_ {
+        self.attackers.keys().copied()
+    }
+
+    /// Returns the number of rooks able to capture the piece on a
+    /// position.
+    pub fn attackers(&self, position: &Position) -> u32 {
+        self.attackers.get(position).copied().unwrap_or(0)
+    }
+
+    /// Adds a piece on the specified square on the board.
+    ///
+    /// # Panics
+    /// The function panics if the square is off the board or already
+    /// occupied, leaving the board and its captures unchanged.
+    pub fn add_piece(&mut self, piece: PieceKind, position: Position) {
+        self.check_free(&position);
+        self.update(&position, |board| board.add_piece(piece, position));
+    }
+
+    /// Removes the piece on the specified square, if any, and returns
+    /// its kind.
+    pub fn remove_piece(&mut self, position: &Position) -> Option<PieceKind> {
+        let kind = self.board.get_piece(position)?;
+        self.update(position, |board| board.remove_piece(position));
+        Some(kind)
+    }
+
+    /// Moves the piece on a square to another one.
+    ///
+    /// # Panics
+    /// The function panics if there is no piece to move, or if the
+    /// destination is off the board or already occupied, leaving the
+    /// board and its captures unchanged.
+    pub fn move_piece(&mut self, from: &Position, to: Position) {
+        assert!(
+            self.board.get_piece(from).is_some(),
+            "Trying to move a piece from an empty square."
+        );
+        self.check_free(&to);
+
+        let kind = self.remove_piece(from).unwrap();
+        self.add_piece(kind, to);
+    }
+
+    /// Replaces all the pieces of the board. Every capture changes, so
+    /// they are all computed again.
+    pub fn set_pieces(&mut self, pieces: Vec<(PieceKind, Position)>) {
+        self.board.set_pieces(pieces);
+        self.recompute();
+    }
+
+    /// Panics if a piece can't be added on a position, before anything
+    /// is changed.
+    fn check_free(&self, position: &Position) {
+        let size = self.board.size();
+        assert!(
+            position.row() < size && position.col() < size,
+            "Trying to add a piece off the board."
+        );
+        assert!(
+            self.board.get_piece(position).is_none(),
+            "Trying to add a piece on an already occupied square."
+        );
+    }
+
+    fn recompute(&mut self) {
+        let report = self.board.get_rooks_capture_report(&self.rules);
+
+        self.attackers = report
+            .targets()
+            .map(|t| (t, report.attackers(&t).len() as u32))
+            .collect();
+    }
+
+    /// Returns the lines of the rooks going through a position, as the
+    /// rook and the direction of the line.
+    fn lines_through(&self, position: &Position) -> Vec<(Position, Direction)> {
+        if !self.rules.is_attacking(PieceKind::Rook) {
+            return Vec::new();
+        }
+
+        Direction::orthogonal()
+            .into_iter()
+            .flat_map(|d| {
+                self.rules
+                    .sight(*position, |p| self.board.first_piece(p, d))
+                    .into_iter()
+                    .filter(|(k, _)| *k == PieceKind::Rook)
+                    .map(move |(_, p)| (p, d.opposite()))
+            })
+            .collect()
+    }
+
+    /// Returns the lines of the rook standing on a position, if any.
+    fn lines_from(&self, position: &Position) -> Vec<(Position, Direction)> {
+        match self.board.get_piece(position) {
+            Some(PieceKind::Rook) => Direction::orthogonal()
+                .into_iter()
+                .map(|d| (*position, d))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Adds, or removes, the captures along lines of rooks.
+    fn count(&mut self, lines: &[(Position, Direction)], add: bool) {
+        lines.iter().for_each(|(rook, d)| {
+            self.board
+                .rook_line(rook, *d, &self.rules)
+                .into_iter()
+                .for_each(|t| {
+                    if add {
+                        *self.attackers.entry(t).or_insert(0) += 1;
+                    } else if let Some(count) = self.attackers.get_mut(&t) {
+                        *count -= 1;
+                        if *count == 0 {
+                            self.attackers.remove(&t);
+                        }
+                    }
+                })
+        });
+    }
+
+    /// Changes the content of a square, updating the captures along the
+    /// lines going through it. The rooks seeing the square are the same
+    /// before and after the change, since the square itself is not
+    /// looked at. The change must not fail, since the counts are
+    /// already lowered when it is made.
+    fn update<F: FnOnce(&mut Board<S>)>(&mut self, position: &Position, change: F) {
+        let through = self.lines_through(position);
+
+        self.count(&through, false);
+        self.count(&self.lines_from(position), false);
+        change(&mut self.board);
+        self.count(&self.lines_from(position), true);
+        self.count(&through, true);
+    }
+}
@@ -1,36 +1,70 @@
 use rand::{prelude::IteratorRandom, Rng};
+use std::collections::HashSet;
 
-/// The four cardinal directions. North and south mean going
-/// up and down the rows (i.e. the numbers), while east and
-/// west mean going up and down the columns (i.e. the letters)
+/// The four cardinal directions, and the four diagonal ones. North
+/// and south mean going up and down the rows (i.e. the numbers), while
+/// east and west mean going up and down the columns (i.e. the letters)
 /// on the chessboard.
-#[derive(Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum Direction {
     North,
     South,
     East,
     West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
 }
 
 impl Direction {
     /// Returns the vector used to calculate a new position based on
-    /// the direction
+    /// the direction, as a number of rows and a number of columns to
+    /// move by.
     ///
     /// # Examples
     /// ```
-    /// assert_eq!(Direction::North.get_vector(), (0, -1))
+    /// # use chess::Direction;
+    /// assert_eq!(Direction::North.get_vector(), (-1, 0));
+    /// assert_eq!(Direction::West.get_vector(), (0, -1));
+    /// assert_eq!(Direction::SouthEast.get_vector(), (1, 1));
     /// ```
     pub fn get_vector(&self) -> (i32, i32) {
         match self {
-            Direction::North => (0, -1),
-            Direction::South => (0, 1),
+            Direction::North => (-1, 0),
+            Direction::South => (1, 0),
             Direction::East => (0, 1),
             Direction::West => (0, -1),
+            Direction::NorthEast => (-1, 1),
+            Direction::NorthWest => (-1, -1),
+            Direction::SouthEast => (1, 1),
+            Direction::SouthWest => (1, -1),
         }
     }
 
-    /// Returns all cardinal directions as a vector.
-    pub fn all() -> Vec<Direction> {
+    /// Returns the direction going the opposite way.
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+            Direction::NorthEast => Direction::SouthWest,
+            Direction::NorthWest => Direction::SouthEast,
+            Direction::SouthEast => Direction::NorthWest,
+            Direction::SouthWest => Direction::NorthEast,
+        }
+    }
+
+    /// Returns the direction whose vector is the one given, if any.
+    pub fn from_vector(vector: (i32, i32)) -> Option<Direction> {
+        Direction::all_with_diagonals()
+            .into_iter()
+            .find(|d| d.get_vector() == vector)
+    }
+
+    /// Returns the four cardinal directions, along which rooks move.
+    pub fn orthogonal() -> Vec<Direction> {
         vec![
             Direction::North,
             Direction::East,
@@ -38,12 +72,43 @@ impl Direction {
             Direction::West,
         ]
     }
+
+    /// Returns the four diagonal directions, along which bishops move.
+    pub fn diagonal() -> Vec<Direction> {
+        vec![
+            Direction::NorthEast,
+            Direction::SouthEast,
+            Direction::SouthWest,
+            Direction::NorthWest,
+        ]
+    }
+
+    /// Returns all cardinal directions as a vector.
+    pub fn all() -> Vec<Direction> {
+        Direction::orthogonal()
+    }
+
+    /// Returns the four cardinal directions followed by the four
+    /// diagonal ones, along which queens and kings move.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chess::Direction;
+    /// assert_eq!(Direction::all().len(), 4);
+    /// assert_eq!(Direction::all_with_diagonals().len(), 8);
+    /// ```
+    pub fn all_with_diagonals() -> Vec<Direction> {
+        let mut directions = Direction::orthogonal();
+        directions.extend(Direction::diagonal());
+        directions
+    }
 }
 
 /// A position on the chessboard, identified by the row and column numbers.
 /// For the sake of simplicity, we consider the columns as numbers, as opposed
 /// to letters in traditional chess notation.
 #[derive(Debug, PartialEq, Copy, Clone, Hash, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
     row: u32,
     col: u32,
@@ -55,9 +120,10 @@ impl Position {
     ///
     /// # Example
     /// ```
-    /// let position = Position::new(3, 4);
+    /// # use chess::Position;
+    /// let position = Position::new(3, 4, 8);
     ///
-    /// assert_eq!(position.row, 3);
+    /// assert_eq!(position.row(), 3);
     /// ```
     ///
     /// # Panics
@@ -69,38 +135,62 @@ impl Position {
         Position { row, col }
     }
 
+    /// Returns the row number of the position.
+    pub fn row(&self) -> u32 {
+        self.row
+    }
+
+    /// Returns the column number of the position.
+    pub fn col(&self) -> u32 {
+        self.col
+    }
+
     /// Returns a list of positions representing a line from the initial position
     /// to the edge of the board, in the direction specified as parameters.
     ///
     /// # Examples
     /// ```
-    /// let position = Position::new(2, 3);
+    /// # use chess::{Direction, Position};
+    /// let position = Position::new(2, 3, 8);
     ///
     /// assert_eq!(
-    ///     position.line(Direction::North),
+    ///     position.line(Direction::West, 8),
     ///     vec![
-    ///         Position::new(2,2),
-    ///         Position::new(2,1),
-    ///         Position::new(2, 0)
+    ///         Position::new(2, 2, 8),
+    ///         Position::new(2, 1, 8),
+    ///         Position::new(2, 0, 8)
     ///     ]
     /// );
+    /// assert_eq!(
+    ///     position.line(Direction::NorthEast, 8),
+    ///     vec![Position::new(1, 4, 8), Position::new(0, 5, 8)]
+    /// );
     /// ```
     pub fn line(&self, direction: Direction, board_size: u32) -> Vec<Position> {
-        match direction {
-            Direction::West => (0..self.col)
-                .map(|i| Position::new(self.row, i, board_size))
-                .rev()
-                .collect(),
-            Direction::East => (board_size.min(self.col + 1)..board_size)
-                .map(|i| Position::new(self.row, i, board_size))
-                .collect(),
-            Direction::South => (board_size.min(self.row + 1)..board_size)
-                .map(|i| Position::new(i, self.col, board_size))
-                .collect(),
-            Direction::North => (0..self.row)
-                .map(|i| Position::new(i, self.col, board_size))
-                .rev()
-                .collect(),
+        self.ray(direction, board_size).collect()
+    }
+
+    /// Returns the same positions as `line`, lazily: the squares are
+    /// computed one after the other by following the vector of the
+    /// direction, until the edge of the board.
+    pub fn ray(&self, direction: Direction, board_size: u32) -> impl Iterator<Item = Position> {
+        let (rows, cols) = direction.get_vector();
+
+        std::iter::successors(self.offset(rows, cols, board_size), move |p| {
+            p.offset(rows, cols, board_size)
+        })
+    }
+
+    /// Returns the position at a given offset of rows and columns from
+    /// this one, if it is on the board.
+    pub fn offset(&self, rows: i32, cols: i32, board_size: u32) -> Option<Position> {
+        let row = self.row as i64 + rows as i64;
+        let col = self.col as i64 + cols as i64;
+
+        if (0..board_size as i64).contains(&row) && (0..board_size as i64).contains(&col) {
+            Some(Position::new(row as u32, col as u32, board_size))
+        } else {
+            None
         }
     }
 
@@ -122,4 +212,32 @@ impl Position {
             .flat_map(|row| (0..max).map(move |col| Position::new(row, col, max)))
             .choose_multiple(random, count as usize)
     }
+
+    /// Generates random unique positions by drawing random squares until
+    /// enough distinct ones are found. Unlike `generate_unique_positions`,
+    /// this never enumerates the squares of the board, which makes it
+    /// suitable for huge boards with few pieces.
+    ///
+    /// # Panics
+    /// The function panics if `count` is greater than the number of
+    /// squares on the board.
+    pub fn generate_sparse_unique_positions<R: Rng>(
+        random: &mut R,
+        count: u32,
+        max: u32,
+    ) -> Vec<Position> {
+        assert!(count as u64 <= max as u64 * max as u64);
+
+        let mut seen = HashSet::with_capacity(count as usize);
+        let mut positions = Vec::with_capacity(count as usize);
+
+        while positions.len() < count as usize {
+            let position = Position::random(random, max);
+            if seen.insert(position) {
+                positions.push(position);
+            }
+        }
+
+        positions
+    }
 }